@@ -1,4 +1,6 @@
-use crate::types::AllMetrics;
+use bustop::types::{
+    AllMetrics, IOReportChannelInfo, MetricsRecord, PowerNode, SmcKeyInfo, SourceStatus,
+};
 
 const BYTES_PER_MB: f64 = 1024.0 * 1024.0;
 const BYTES_PER_GB: f64 = 1024.0 * 1024.0 * 1024.0;
@@ -12,7 +14,9 @@ pub fn print_header(cpu_brand: &str, interval_ms: u64) {
     println!();
 }
 
-pub fn print_metrics(metrics: &AllMetrics, first: bool) {
+/// `per_core` adds the per-core table to CPU FABRIC, `per_sensor` the
+/// per-sensor table to THERMAL.
+pub fn print_metrics(metrics: &AllMetrics, first: bool, per_core: bool, per_sensor: bool) {
     if first {
         // Need at least one interval to compute rates
         println!("Collecting initial sample...");
@@ -33,19 +37,41 @@ pub fn print_metrics(metrics: &AllMetrics, first: bool) {
     println!();
 
     // CPU Fabric section
-    print_cpu_section(metrics);
+    print_cpu_section(metrics, per_core);
     println!();
 
     // GPU section
     print_gpu_section(metrics);
     println!();
 
+    // DRAM traffic, on chips with memory-controller counters
+    if metrics.memory_bandwidth.is_some() {
+        print_bandwidth_section(metrics);
+        println!();
+    }
+
     // Storage section
     print_storage_section(metrics);
     println!();
 
+    // Power breakdown, when the platform reports per-component energy
+    if !metrics.power_tree.is_empty() {
+        print_power_section(metrics);
+        println!();
+    }
+
+    // Temperatures and fans, where the machine reports them
+    let thermal = &metrics.thermal;
+    if !thermal.groups.is_empty() || !thermal.fans.is_empty() {
+        print_thermal_section(metrics, per_sensor);
+        println!();
+    }
+
     // System section
     print_system_section(metrics);
+
+    // Only shown when something is missing, so zeros above can be explained
+    print_sources_section(metrics);
 }
 
 fn print_memory_section(metrics: &AllMetrics) {
@@ -70,7 +96,7 @@ fn print_memory_section(metrics: &AllMetrics) {
     );
 }
 
-fn print_cpu_section(metrics: &AllMetrics) {
+fn print_cpu_section(metrics: &AllMetrics, per_core: bool) {
     if metrics.cpu_clusters.is_empty() {
         println!("CPU FABRIC");
         println!("  (no data available)");
@@ -78,14 +104,22 @@ fn print_cpu_section(metrics: &AllMetrics) {
     }
 
     println!("CPU FABRIC");
+    // Multi-die names ("D1-P0-Cluster") are wider than the usual column
+    let width = metrics
+        .cpu_clusters
+        .iter()
+        .map(|c| c.name.len())
+        .max()
+        .unwrap_or(0)
+        .max(12);
     println!(
-        "{:<12} {:>10} {:>10} {:>10} {:>10}",
+        "{:<width$} {:>10} {:>10} {:>10} {:>10}",
         "cluster", "freq_MHz", "active%", "idle%", "power_W"
     );
 
     for cluster in &metrics.cpu_clusters {
         println!(
-            "{:<12} {:>10} {:>10.1} {:>10.1} {:>10.2}",
+            "{:<width$} {:>10} {:>10.1} {:>10.1} {:>10.2}",
             cluster.name,
             if cluster.freq_mhz > 0 {
                 cluster.freq_mhz.to_string()
@@ -97,6 +131,47 @@ fn print_cpu_section(metrics: &AllMetrics) {
             cluster.power_watts
         );
     }
+
+    if per_core && !metrics.cpu_cores.is_empty() {
+        println!(
+            "{:<8} {:<width$} {:>10} {:>10}",
+            "core", "cluster", "freq_MHz", "active%"
+        );
+        for core in &metrics.cpu_cores {
+            println!(
+                "{:<8} {:<width$} {:>10} {:>10.1}",
+                format!("cpu{}", core.id),
+                core.cluster,
+                if core.freq_mhz > 0 {
+                    core.freq_mhz.to_string()
+                } else {
+                    "-".to_string()
+                },
+                core.active_pct
+            );
+        }
+    }
+
+    if !metrics.cpu_rollups.is_empty() {
+        println!(
+            "{:<width$} {:>10} {:>10} {:>10} {:>10}",
+            "rollup", "freq_MHz", "active%", "clusters", "power_W"
+        );
+        for rollup in &metrics.cpu_rollups {
+            println!(
+                "{:<width$} {:>10} {:>10.1} {:>10} {:>10.2}",
+                rollup.name,
+                if rollup.freq_mhz > 0 {
+                    rollup.freq_mhz.to_string()
+                } else {
+                    "-".to_string()
+                },
+                rollup.active_pct,
+                rollup.clusters,
+                rollup.power_watts
+            );
+        }
+    }
 }
 
 fn print_gpu_section(metrics: &AllMetrics) {
@@ -120,6 +195,23 @@ fn print_gpu_section(metrics: &AllMetrics) {
         gpu.power_watts
     );
 
+    // Residency per P-state, only the states the GPU actually visited
+    let visited: Vec<String> = gpu
+        .pstate_residency
+        .iter()
+        .filter(|p| p.residency_pct >= 0.05)
+        .map(|p| {
+            if p.freq_mhz > 0 {
+                format!("{}@{} {:.1}%", p.state, p.freq_mhz, p.residency_pct)
+            } else {
+                format!("{} {:.1}%", p.state, p.residency_pct)
+            }
+        })
+        .collect();
+    if !visited.is_empty() {
+        println!("  {:<10} {}", "pstates", visited.join("  "));
+    }
+
     if metrics.ane.power_watts > 0.0 {
         println!(
             "{:<12} {:>10} {:>10} {:>10.2}",
@@ -128,6 +220,34 @@ fn print_gpu_section(metrics: &AllMetrics) {
     }
 }
 
+fn print_bandwidth_section(metrics: &AllMetrics) {
+    let Some(bandwidth) = &metrics.memory_bandwidth else {
+        return;
+    };
+
+    println!("MEMORY BANDWIDTH");
+    println!(
+        "{:<12} {:>12} {:>12} {:>12}",
+        "agent", "read_GB/s", "write_GB/s", "total_GB/s"
+    );
+    for agent in &bandwidth.agents {
+        println!(
+            "{:<12} {:>12.2} {:>12.2} {:>12.2}",
+            agent.name,
+            agent.read_gbps,
+            agent.write_gbps,
+            agent.read_gbps + agent.write_gbps
+        );
+    }
+    println!(
+        "{:<12} {:>12.2} {:>12.2} {:>12.2}",
+        "total",
+        bandwidth.read_gbps,
+        bandwidth.write_gbps,
+        bandwidth.read_gbps + bandwidth.write_gbps
+    );
+}
+
 fn print_storage_section(metrics: &AllMetrics) {
     if metrics.disks.is_empty() {
         println!("STORAGE");
@@ -136,20 +256,116 @@ fn print_storage_section(metrics: &AllMetrics) {
     }
 
     println!("STORAGE");
-    println!(
+
+    // Busy-time columns only where the platform reports them
+    let extended = metrics.disks.iter().any(|d| d.util_pct.is_some());
+
+    print!(
         "{:<12} {:>12} {:>12} {:>10} {:>10}",
         "device", "read_MB/s", "write_MB/s", "r_ops/s", "w_ops/s"
     );
+    if extended {
+        print!(" {:>8} {:>10} {:>8}", "util%", "await_ms", "aqu-sz");
+    }
+    println!();
 
     for disk in &metrics.disks {
         let read_mb = disk.read_bytes_per_sec as f64 / BYTES_PER_MB;
         let write_mb = disk.write_bytes_per_sec as f64 / BYTES_PER_MB;
 
-        println!(
+        print!(
             "{:<12} {:>12.2} {:>12.2} {:>10} {:>10}",
             disk.name, read_mb, write_mb, disk.read_ops_per_sec, disk.write_ops_per_sec
         );
+        if extended {
+            print!(
+                " {:>8} {:>10} {:>8}",
+                format_optional(disk.util_pct, 1),
+                format_optional(disk.await_ms, 2),
+                format_optional(disk.queue_depth, 2)
+            );
+        }
+        println!();
+    }
+}
+
+fn format_optional(value: Option<f64>, precision: usize) -> String {
+    match value {
+        Some(v) => format!("{:.*}", precision, v),
+        None => "-".to_string(),
+    }
+}
+
+fn print_power_section(metrics: &AllMetrics) {
+    println!("POWER");
+    println!("{:<28} {:>10} {:>10}", "component", "power_W", "energy_J");
+    for node in &metrics.power_tree {
+        print_power_node(node, 0);
+    }
+}
+
+fn print_power_node(node: &PowerNode, depth: usize) {
+    let label = format!("{:indent$}{}", "", node.name, indent = depth * 2);
+    println!("{:<28} {:>10.3} {:>10.3}", label, node.watts, node.joules);
+    for child in &node.children {
+        print_power_node(child, depth + 1);
+    }
+}
+
+fn print_thermal_section(metrics: &AllMetrics, per_sensor: bool) {
+    let thermal = &metrics.thermal;
+
+    println!("THERMAL");
+    if !thermal.groups.is_empty() {
+        println!(
+            "{:<12} {:>8} {:>8} {:>8} {:>8} {:>8}",
+            "group", "sensors", "min_C", "avg_C", "max_C", "peak_C"
+        );
+        for group in &thermal.groups {
+            println!(
+                "{:<12} {:>8} {:>8.1} {:>8.1} {:>8.1} {:>8.1}",
+                group.name, group.sensors, group.min_c, group.avg_c, group.max_c, group.peak_c
+            );
+        }
+    }
+
+    if per_sensor && !thermal.sensors.is_empty() {
+        let name_w = thermal
+            .sensors
+            .iter()
+            .map(|s| s.name.len())
+            .max()
+            .unwrap_or(0)
+            .max("sensor".len());
+        println!(
+            "{:<name_w$}  {:<4}  {:<12} {:>8}",
+            "sensor", "key", "group", "temp_C"
+        );
+        for sensor in &thermal.sensors {
+            println!(
+                "{:<name_w$}  {:<4}  {:<12} {:>8.1}",
+                sensor.name, sensor.key, sensor.group, sensor.temp_c
+            );
+        }
     }
+
+    if !thermal.fans.is_empty() {
+        println!(
+            "{:<12} {:>10} {:>10} {:>10} {:>10}",
+            "fan", "rpm", "min_rpm", "max_rpm", "target"
+        );
+        for fan in &thermal.fans {
+            println!(
+                "{:<12} {:>10.0} {:>10.0} {:>10.0} {:>10.0}",
+                format!("fan{}", fan.id),
+                fan.rpm,
+                fan.min_rpm,
+                fan.max_rpm,
+                fan.target_rpm
+            );
+        }
+    }
+    println!("pressure: {}", thermal.pressure);
 }
 
 fn print_system_section(metrics: &AllMetrics) {
@@ -169,10 +385,95 @@ fn print_system_section(metrics: &AllMetrics) {
         sys.dram_power_watts,
         sys.thermal_pressure
     );
+
+    // Whole-machine power, on Macs whose SMC reports it
+    if let Some(wall) = sys.wall_power_watts {
+        println!(
+            "{:>12} {:>12} {:>12} {:>16}",
+            "wall_W", "adapter_W", "battery_W", "unattributed_W"
+        );
+        println!(
+            "{:>12.2} {:>12} {:>12} {:>16}",
+            wall,
+            format_optional(sys.adapter_power_watts, 2),
+            format_optional(sys.battery_power_watts, 2),
+            format_optional(sys.unattributed_power_watts, 2)
+        );
+    }
+}
+
+fn print_sources_section(metrics: &AllMetrics) {
+    let unhealthy: Vec<_> = metrics
+        .sources
+        .iter()
+        .filter(|s| s.status != SourceStatus::Available)
+        .collect();
+    if unhealthy.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("SOURCES");
+    println!("{:>12} {:>10} {:>9}  error", "source", "status", "failures");
+    for source in unhealthy {
+        println!(
+            "{:>12} {:>10} {:>9}  {}",
+            source.name,
+            source.status.to_string(),
+            source.consecutive_failures,
+            source.last_error.as_deref().unwrap_or("-")
+        );
+    }
+}
+
+/// `bustop ioreport list`: one row per channel, columns sized to fit.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub fn print_ioreport_channels(channels: &[IOReportChannelInfo]) {
+    let width = |field: fn(&IOReportChannelInfo) -> &str, header: &str| {
+        channels
+            .iter()
+            .map(|c| field(c).len())
+            .max()
+            .unwrap_or(0)
+            .max(header.len())
+    };
+    let group_w = width(|c| &c.group, "group");
+    let subgroup_w = width(|c| &c.subgroup, "subgroup");
+    let channel_w = width(|c| &c.channel, "channel");
+
+    println!(
+        "{:<group_w$}  {:<subgroup_w$}  {:<channel_w$}  {:<9}  unit",
+        "group", "subgroup", "channel", "format"
+    );
+    for c in channels {
+        println!(
+            "{:<group_w$}  {:<subgroup_w$}  {:<channel_w$}  {:<9}  {}",
+            c.group,
+            c.subgroup,
+            c.channel,
+            c.format.to_string(),
+            c.unit
+        );
+    }
+}
+
+/// `bustop smc dump`: one row per key. Unreadable values show as "-".
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub fn print_smc_keys(keys: &[SmcKeyInfo]) {
+    println!("{:<4}  {:<4}  {:>4}  value", "key", "type", "size");
+    for k in keys {
+        println!(
+            "{:<4}  {:<4}  {:>4}  {}",
+            k.key,
+            k.data_type,
+            k.size,
+            k.display.as_deref().unwrap_or("-")
+        );
+    }
 }
 
-pub fn print_json(metrics: &AllMetrics) {
-    match serde_json::to_string(metrics) {
+pub fn print_json(record: &MetricsRecord) {
+    match serde_json::to_string(record) {
         Ok(json) => println!("{}", json),
         Err(e) => eprintln!("Error serializing to JSON: {}", e),
     }
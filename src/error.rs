@@ -0,0 +1,61 @@
+use std::fmt;
+
+/// Errors surfaced by the collector and its sources.
+#[derive(Debug)]
+pub enum Error {
+    /// The host description (CPU brand, core counts, memory size) could not
+    /// be read.
+    HostInfo(String),
+    /// A source name passed to `disable_source` is not registered.
+    UnknownSource {
+        name: String,
+        available: Vec<&'static str>,
+    },
+    /// The OS interface a source reads from is missing or not accessible.
+    Unavailable(String),
+    /// A reading failed on a source that initialized fine.
+    Read(String),
+    /// An `--ioreport-channel` name is not "Group/Subgroup/Channel".
+    InvalidChannel(String),
+    /// A configuration file could not be read or is malformed.
+    Config(String),
+    /// In strict mode, a required source failed to initialize.
+    SourceInit {
+        name: &'static str,
+        error: Box<Error>,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::HostInfo(msg) => write!(f, "failed to read host info: {}", msg),
+            Error::UnknownSource { name, available } => write!(
+                f,
+                "unknown source '{}' (available: {})",
+                name,
+                available.join(", ")
+            ),
+            Error::Unavailable(msg) | Error::Read(msg) | Error::Config(msg) => {
+                write!(f, "{}", msg)
+            }
+            Error::InvalidChannel(spec) => write!(
+                f,
+                "invalid channel '{}' (expected Group/Subgroup/Channel)",
+                spec
+            ),
+            Error::SourceInit { name, error } => {
+                write!(f, "source '{}' is unavailable: {}", name, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::SourceInit { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,36 @@
+//! Hardware metrics for Apple Silicon Macs and Linux, as a library.
+//!
+//! The `bustop` binary is a thin front end over [`MetricsCollector`]: build
+//! one, call [`MetricsCollector::collect`] once per interval, and render the
+//! returned [`types::AllMetrics`].
+//!
+//! ```no_run
+//! let mut collector = bustop::MetricsCollector::builder().build().unwrap();
+//! loop {
+//!     let metrics = collector.collect();
+//!     println!("{:.1} W", metrics.system.total_power_watts);
+//!     std::thread::sleep(collector.interval());
+//! }
+//! ```
+//!
+//! Custom readings plug in through [`sources::MetricSource`]; see
+//! [`CollectorBuilder`] for a complete example.
+//!
+//! # Stability
+//!
+//! Within 0.x, [`MetricsCollector`], [`CollectorBuilder`], the
+//! [`sources::MetricSource`] trait, [`sources::Capability`],
+//! [`sources::SampleContext`] and the JSON field names of [`types`] only
+//! change in minor releases. The structs in [`types`] may gain fields in any
+//! release, so construct them with `..Default::default()`. The platform
+//! readers behind the sources (IOReport, SMC, procfs) are not public API.
+
+mod error;
+mod metrics;
+pub mod sources;
+#[cfg(test)]
+mod test_util;
+pub mod types;
+
+pub use error::Error;
+pub use metrics::{CollectorBuilder, MetricsCollector};
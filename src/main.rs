@@ -1,11 +1,13 @@
 mod display;
-mod metrics;
-mod sources;
-mod types;
+mod record;
 
-use clap::Parser;
-use metrics::MetricsCollector;
+use clap::{Parser, Subcommand};
+use bustop::sources::{self, DiskStats, SimulatedSource};
+use bustop::types::{self, CpuRollup};
+use bustop::MetricsCollector;
+use record::{Recorder, Recording, RecordingHeader, RECORDING_VERSION};
 use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
@@ -14,23 +16,146 @@ use std::time::Duration;
 #[command(name = "bustop")]
 #[command(author = "bustop")]
 #[command(version = "0.1.0")]
-#[command(about = "Bus and interconnect utilization monitor for macOS", long_about = None)]
+#[command(about = "Bus and interconnect utilization monitor for macOS and Linux", long_about = None)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Sample interval in milliseconds
-    #[arg(short = 'i', long = "interval", default_value_t = 1000)]
+    #[arg(short = 'i', long = "interval", default_value_t = 1000, global = true)]
     interval: u64,
 
     /// Number of samples to collect (0 = infinite)
-    #[arg(short = 'n', long = "count", default_value_t = 0)]
+    #[arg(short = 'n', long = "count", default_value_t = 0, global = true)]
     count: u64,
 
     /// Output in JSON format (one object per line)
-    #[arg(short = 'j', long = "json")]
+    #[arg(short = 'j', long = "json", global = true)]
     json: bool,
 
     /// Don't clear screen between updates (append mode)
-    #[arg(short = 'a', long = "append")]
+    #[arg(short = 'a', long = "append", global = true)]
     append: bool,
+
+    /// Skip a metric source (repeatable), e.g. --disable-source smc
+    #[arg(long = "disable-source", value_name = "SOURCE", global = true)]
+    disable_source: Vec<String>,
+
+    /// Exit with an error if any requested source is unavailable
+    #[arg(long = "strict", global = true)]
+    strict: bool,
+
+    /// Add a per-core table to CPU FABRIC
+    #[arg(long = "per-core", global = true)]
+    per_core: bool,
+
+    /// Add a per-sensor table to THERMAL
+    #[arg(long = "per-sensor", global = true)]
+    per_sensor: bool,
+
+    /// Add per-die and per-core-type summaries to CPU FABRIC and the JSON
+    #[arg(long = "cpu-rollup", global = true)]
+    cpu_rollup: bool,
+
+    /// Include partitions and loop devices in STORAGE (Linux)
+    #[arg(long = "all-disks", global = true, conflicts_with = "simulate")]
+    all_disks: bool,
+
+    /// Replace the hardware sources with a deterministic synthetic generator
+    #[arg(long = "simulate", global = true)]
+    simulate: bool,
+
+    /// Also report this IOReport channel's raw deltas in the JSON
+    /// `raw_channels` map (repeatable), e.g. "GPU Stats/GPU Performance States/GPUPH"
+    #[arg(
+        long = "ioreport-channel",
+        value_name = "GROUP/SUBGROUP/CHANNEL",
+        global = true,
+        conflicts_with = "simulate"
+    )]
+    ioreport_channel: Vec<String>,
+
+    /// Rename, regroup or add SMC temperature sensors from this TOML file
+    /// (default: ~/.config/bustop/sensors.toml, if present)
+    #[arg(
+        long = "sensor-config",
+        value_name = "PATH",
+        global = true,
+        conflicts_with = "simulate"
+    )]
+    sensor_config: Option<PathBuf>,
+
+    /// Seed for --simulate
+    #[arg(
+        long = "seed",
+        default_value_t = 0,
+        global = true,
+        requires = "simulate"
+    )]
+    seed: u64,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Write timestamped samples to a file for later replay
+    Record {
+        /// Recording file to create
+        path: PathBuf,
+    },
+    /// Print the JSON Schema of `--json` records
+    Schema,
+    /// Play a recording back through the table, append or JSON output
+    Replay {
+        /// Recording file written by `bustop record`
+        path: PathBuf,
+
+        /// Playback speed multiplier (2 = twice as fast)
+        #[arg(long = "speed", default_value_t = 1.0)]
+        speed: f64,
+
+        /// Render all samples without waiting
+        #[arg(long = "instant", conflicts_with = "speed")]
+        instant: bool,
+    },
+    /// Explore the IOReport channels of this machine (macOS)
+    Ioreport {
+        #[command(subcommand)]
+        command: IOReportCommand,
+    },
+    /// Explore the SMC keys of this machine (macOS)
+    Smc {
+        #[command(subcommand)]
+        command: SmcCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum IOReportCommand {
+    /// List groups, subgroups and channels with their format and unit
+    List {
+        /// Only list this group
+        #[arg(long = "group")]
+        group: Option<String>,
+    },
+    /// Sample channels over one interval and write their deltas as a test fixture
+    Dump {
+        /// Fixture file to create
+        path: PathBuf,
+
+        /// Only dump this group
+        #[arg(long = "group")]
+        group: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum SmcCommand {
+    /// Print every key with its type, size and current value
+    Dump {
+        /// Only print keys matching this glob, e.g. "T*" or "F?Ac"
+        #[arg(long = "filter", value_name = "GLOB")]
+        filter: Option<String>,
+    },
 }
 
 fn main() {
@@ -42,15 +167,207 @@ fn main() {
 
     ctrlc_handler(r);
 
-    // Initialize metrics collector
-    let interval = Duration::from_millis(args.interval);
-    let mut collector = match MetricsCollector::new(interval) {
-        Ok(c) => c,
+    match &args.command {
+        None => run_live(&args, &running),
+        Some(Command::Record { path }) => run_record(&args, path, &running),
+        Some(Command::Schema) => print_schema(),
+        Some(Command::Ioreport { command }) => run_ioreport(&args, command),
+        Some(Command::Smc { command }) => run_smc(&args, command),
+        Some(Command::Replay {
+            path,
+            speed,
+            instant,
+        }) => {
+            if !instant && (!speed.is_finite() || *speed <= 0.0) {
+                eprintln!("--speed must be a positive number");
+                std::process::exit(2);
+            }
+            run_replay(
+                &args,
+                path,
+                if *instant { None } else { Some(*speed) },
+                &running,
+            )
+        }
+    }
+}
+
+fn build_collector(args: &Args) -> MetricsCollector {
+    let mut builder = MetricsCollector::builder()
+        .interval(Duration::from_millis(args.interval))
+        .strict(args.strict);
+
+    if args.simulate {
+        builder = builder
+            .without_platform_sources()
+            .host_info(SimulatedSource::host_info())
+            .source(Box::new(
+                SimulatedSource::new(args.seed).interval(Duration::from_millis(args.interval)),
+            ));
+    } else if args.all_disks {
+        builder = builder.source(Box::new(DiskStats::new().include_all_devices()));
+    }
+    if !args.ioreport_channel.is_empty() {
+        builder = builder.source(ioreport_source(args));
+    }
+    if !args.simulate {
+        if let Some(source) = smc_source(args) {
+            builder = builder.source(source);
+        }
+    }
+    for name in &args.disable_source {
+        builder = builder.disable_source(name.as_str());
+    }
+
+    match builder.build() {
+        Ok(collector) => collector,
+        Err(e @ bustop::Error::UnknownSource { .. }) => {
+            eprintln!("Invalid --disable-source: {}", e);
+            std::process::exit(2);
+        }
         Err(e) => {
             eprintln!("Failed to initialize metrics collector: {}", e);
             std::process::exit(1);
         }
+    }
+}
+
+/// The IOReport source with the `--ioreport-channel` passthroughs.
+#[cfg(target_os = "macos")]
+fn ioreport_source(args: &Args) -> Box<dyn sources::MetricSource> {
+    match sources::IOReportSource::new().with_raw_channels(&args.ioreport_channel) {
+        Ok(source) => Box::new(source),
+        Err(e) => {
+            eprintln!("Invalid --ioreport-channel: {}", e);
+            std::process::exit(2);
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn ioreport_source(_args: &Args) -> Box<dyn sources::MetricSource> {
+    eprintln!("--ioreport-channel needs IOReport, which is only available on macOS");
+    std::process::exit(2);
+}
+
+/// The SMC source with the user's sensor config, from `--sensor-config` or
+/// the default location. `None` leaves the built-in sensor table in place.
+#[cfg(target_os = "macos")]
+fn smc_source(args: &Args) -> Option<Box<dyn sources::MetricSource>> {
+    let path = match &args.sensor_config {
+        Some(path) => path.clone(),
+        None => default_sensor_config().filter(|path| path.exists())?,
     };
+    let cpu_brand = sources::SysctlInfo::new()
+        .map(|info| info.cpu_brand)
+        .unwrap_or_default();
+
+    match sources::SmcSource::new(&cpu_brand).with_sensor_config(&path) {
+        Ok(source) => Some(Box::new(source)),
+        Err(e) => {
+            eprintln!("Invalid sensor config: {}", e);
+            std::process::exit(2);
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn smc_source(args: &Args) -> Option<Box<dyn sources::MetricSource>> {
+    if args.sensor_config.is_some() {
+        eprintln!("--sensor-config needs the SMC, which is only available on macOS");
+        std::process::exit(2);
+    }
+    None
+}
+
+/// `$XDG_CONFIG_HOME/bustop/sensors.toml`, or `~/.config/bustop/sensors.toml`.
+#[cfg(target_os = "macos")]
+fn default_sensor_config() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(config_dir.join("bustop").join("sensors.toml"))
+}
+
+#[cfg(target_os = "macos")]
+fn run_ioreport(args: &Args, command: &IOReportCommand) {
+    use sources::IOReportSource;
+
+    match command {
+        IOReportCommand::List { group } => {
+            let channels = match IOReportSource::list_channels(group.as_deref()) {
+                Ok(channels) => channels,
+                Err(e) => {
+                    eprintln!("Failed to list IOReport channels: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            if args.json {
+                match serde_json::to_string_pretty(&channels) {
+                    Ok(json) => println!("{}", json),
+                    Err(e) => {
+                        eprintln!("Error serializing channels: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            } else {
+                display::print_ioreport_channels(&channels);
+            }
+        }
+        IOReportCommand::Dump { path, group } => {
+            let interval = Duration::from_millis(args.interval);
+            match IOReportSource::dump_fixture(path, group.as_deref(), interval) {
+                Ok(count) => eprintln!("Wrote {} channels to {}", count, path.display()),
+                Err(e) => {
+                    eprintln!("Failed to dump IOReport channels: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn run_ioreport(_args: &Args, _command: &IOReportCommand) {
+    eprintln!("IOReport is only available on macOS");
+    std::process::exit(1);
+}
+
+#[cfg(target_os = "macos")]
+fn run_smc(args: &Args, command: &SmcCommand) {
+    match command {
+        SmcCommand::Dump { filter } => {
+            let keys = match sources::SmcSource::list_keys(filter.as_deref()) {
+                Ok(keys) => keys,
+                Err(e) => {
+                    eprintln!("Failed to read SMC keys: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            if args.json {
+                match serde_json::to_string_pretty(&keys) {
+                    Ok(json) => println!("{}", json),
+                    Err(e) => {
+                        eprintln!("Error serializing keys: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            } else {
+                display::print_smc_keys(&keys);
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn run_smc(_args: &Args, _command: &SmcCommand) {
+    eprintln!("The SMC is only available on macOS");
+    std::process::exit(1);
+}
+
+fn run_live(args: &Args, running: &AtomicBool) {
+    let mut collector = build_collector(args);
 
     // Initial header for non-JSON mode
     if !args.json && !args.append {
@@ -63,19 +380,10 @@ fn main() {
     while running.load(Ordering::SeqCst) {
         // Collect metrics
         let metrics = collector.collect();
+        let mut record = collector.record(metrics);
+        add_rollups(args, &mut record);
 
-        // Output
-        if args.json {
-            if !first {
-                display::print_json(&metrics);
-            }
-        } else if args.append {
-            if !first {
-                print_append_mode(&metrics);
-            }
-        } else {
-            display::print_metrics(&metrics, first);
-        }
+        render(args, &record, first);
 
         first = false;
         sample_count += 1;
@@ -89,7 +397,120 @@ fn main() {
         io::stdout().flush().ok();
 
         // Sleep until next sample
-        std::thread::sleep(interval);
+        std::thread::sleep(collector.interval());
+    }
+}
+
+fn run_record(args: &Args, path: &Path, running: &AtomicBool) {
+    let mut collector = build_collector(args);
+
+    let header = RecordingHeader {
+        version: RECORDING_VERSION,
+        bustop_version: env!("CARGO_PKG_VERSION").to_string(),
+        host: sources::hostname(),
+        cpu_brand: collector.cpu_brand().to_string(),
+        interval_ms: args.interval,
+    };
+    let mut recorder = match Recorder::create(path, &header) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Failed to create recording: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut recorded: u64 = 0;
+    let mut first = true;
+
+    while running.load(Ordering::SeqCst) {
+        let metrics = collector.collect();
+        let mut record = collector.record(metrics);
+        add_rollups(args, &mut record);
+
+        // The first sample has no deltas yet, same as the live view
+        if !first {
+            if let Err(e) = recorder.write(&record) {
+                eprintln!("Failed to write recording: {}", e);
+                std::process::exit(1);
+            }
+            recorded += 1;
+        }
+        first = false;
+
+        if args.count > 0 && recorded >= args.count {
+            break;
+        }
+
+        std::thread::sleep(collector.interval());
+    }
+
+    eprintln!("Recorded {} samples to {}", recorded, path.display());
+}
+
+/// Feed a recording through the normal renderers. `speed` of `None` renders
+/// every sample back to back.
+fn run_replay(args: &Args, path: &Path, speed: Option<f64>, running: &AtomicBool) {
+    let recording = match Recording::load(path) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Failed to load recording: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if !args.json && !args.append {
+        display::print_header(&recording.header.cpu_brand, recording.header.interval_ms);
+    }
+
+    let mut prev_timestamp: Option<u64> = None;
+
+    for (idx, mut record) in recording.samples.into_iter().enumerate() {
+        if !running.load(Ordering::SeqCst) || (args.count > 0 && idx as u64 >= args.count) {
+            break;
+        }
+
+        // Keep the original spacing between samples, scaled by speed
+        let timestamp_ms = record.metrics.timestamp_ms;
+        if let (Some(speed), Some(prev)) = (speed, prev_timestamp) {
+            let gap_ms = timestamp_ms.saturating_sub(prev) as f64 / speed;
+            std::thread::sleep(Duration::from_secs_f64(gap_ms / 1000.0));
+        }
+        prev_timestamp = Some(timestamp_ms);
+
+        add_rollups(args, &mut record);
+        render(args, &record, false);
+        io::stdout().flush().ok();
+    }
+}
+
+fn render(args: &Args, record: &types::MetricsRecord, first: bool) {
+    if args.json {
+        if !first {
+            display::print_json(record);
+        }
+    } else if args.append {
+        if !first {
+            print_append_mode(&record.metrics);
+        }
+    } else {
+        display::print_metrics(&record.metrics, first, args.per_core, args.per_sensor);
+    }
+}
+
+fn add_rollups(args: &Args, record: &mut types::MetricsRecord) {
+    if args.cpu_rollup && record.metrics.cpu_rollups.is_empty() {
+        record.metrics.cpu_rollups = CpuRollup::from_clusters(&record.metrics.cpu_clusters);
+    }
+}
+
+fn print_schema() {
+    let schema = schemars::schema_for!(types::MetricsRecord);
+    match serde_json::to_string_pretty(&schema) {
+        Ok(json) => println!("{}", json),
+        Err(e) => {
+            eprintln!("Error serializing schema: {}", e);
+            std::process::exit(1);
+        }
     }
 }
 
@@ -114,13 +535,26 @@ fn print_append_mode(metrics: &types::AllMetrics) {
         print!("{}: {:.1}% | ", cluster.name, cluster.active_pct);
     }
 
-    print!("gpu: {:.1}% | ", metrics.gpu.active_pct);
+    if metrics.gpu.freq_mhz > 0 {
+        print!(
+            "gpu: {:.1}% @ {}MHz | ",
+            metrics.gpu.active_pct, metrics.gpu.freq_mhz
+        );
+    } else {
+        print!("gpu: {:.1}% | ", metrics.gpu.active_pct);
+    }
 
     if sys.total_power_watts > 0.0 {
         print!("power: {:.1}W | ", sys.total_power_watts);
     }
+    if let Some(wall) = sys.wall_power_watts {
+        print!("wall: {:.1}W | ", wall);
+    }
 
-    for disk in &metrics.disks {
+    for (i, disk) in metrics.disks.iter().enumerate() {
+        if i > 0 {
+            print!(" | ");
+        }
         print!(
             "{}: {:.1}/{:.1} MB/s",
             disk.name,
@@ -1,50 +1,87 @@
-use crate::sources::{DiskStats, IOReport, MemoryStats, Smc, SysctlInfo};
+use crate::error::Error;
+use crate::sources::{self, Capability, MetricSource, SampleContext, SourceRegistry, SysctlInfo};
 use crate::types::*;
+use std::collections::VecDeque;
 use std::time::{Duration, Instant};
 
+const DEFAULT_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// Samples every registered source and merges the results into `AllMetrics`.
+///
+/// Rates are computed over the time actually elapsed between two `collect`
+/// calls, so the first call after construction only primes the counters.
 pub struct MetricsCollector {
-    ioreport: Option<IOReport>,
-    smc: Option<Smc>,
-    memory_stats: MemoryStats,
-    disk_stats: DiskStats,
+    sources: Vec<SourceSlot>,
     sysctl_info: SysctlInfo,
     last_sample: Instant,
     interval: Duration,
+    thermal_history: ThermalHistory,
 }
 
 impl MetricsCollector {
-    pub fn new(interval: Duration) -> Result<Self, String> {
-        let sysctl_info = SysctlInfo::new()?;
-
-        // Initialize IOReport with relevant channel groups
-        let ioreport = IOReport::new(&[
-            ("Energy Model", None),
-            ("CPU Stats", Some("CPU Complex Performance States")),
-            ("CPU Stats", Some("CPU Core Performance States")),
-            ("GPU Stats", Some("GPU Performance States")),
-        ])
-        .ok();
-
-        let smc = Smc::new().ok();
-
-        let memory_stats = MemoryStats::new(sysctl_info.page_size, sysctl_info.physical_memory);
-        let disk_stats = DiskStats::new();
-
-        Ok(Self {
-            ioreport,
-            smc,
-            memory_stats,
-            disk_stats,
+    /// Start configuring a collector with the platform's default sources.
+    pub fn builder() -> CollectorBuilder {
+        CollectorBuilder::new()
+    }
+
+    /// Initialize every registered source. Sources that fail to initialize
+    /// are kept as `Failed` in the health report and their slice of
+    /// `AllMetrics` stays at its default.
+    pub fn new(sysctl_info: SysctlInfo, registry: SourceRegistry, interval: Duration) -> Self {
+        let sources = registry
+            .into_sources()
+            .into_iter()
+            .map(SourceSlot::init)
+            .collect();
+
+        Self {
+            sources,
             sysctl_info,
             last_sample: Instant::now(),
             interval,
+            thermal_history: ThermalHistory::default(),
+        }
+    }
+
+    /// The sampling interval the collector was configured with.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Names of the sources that initialized successfully, in sampling order.
+    pub fn source_names(&self) -> Vec<&'static str> {
+        self.sources
+            .iter()
+            .filter(|slot| slot.init_error.is_none())
+            .map(|slot| slot.source.name())
+            .collect()
+    }
+
+    /// Current status of every registered source, as reported in
+    /// `AllMetrics::sources`.
+    pub fn health(&self) -> Vec<SourceHealth> {
+        self.sources
+            .iter()
+            .map(|slot| slot.health.clone())
+            .collect()
+    }
+
+    /// The init error of the first source that failed and is required:
+    /// named in `requested`, or not `optional`.
+    fn take_required_init_error(&mut self, requested: &[&str]) -> Option<Error> {
+        let slot = self.sources.iter_mut().find(|slot| {
+            let required = requested.contains(&slot.source.name()) || !slot.source.optional();
+            required && slot.init_error.is_some()
+        })?;
+        Some(Error::SourceInit {
+            name: slot.source.name(),
+            error: Box::new(slot.init_error.take()?),
         })
     }
 
     pub fn collect(&mut self) -> AllMetrics {
         let now = Instant::now();
         let actual_interval = now.duration_since(self.last_sample);
-        let interval_secs = actual_interval.as_secs_f64();
         self.last_sample = now;
 
         let timestamp_ms = std::time::SystemTime::now()
@@ -52,183 +89,527 @@ impl MetricsCollector {
             .map(|d| d.as_millis() as u64)
             .unwrap_or(0);
 
-        // Collect from each source
-        let memory = self.memory_stats.get_metrics();
-        let disks = self.disk_stats.get_metrics(interval_secs);
-
-        // Parse IOReport samples
-        let (cpu_clusters, gpu, ane, system) = self.collect_ioreport_metrics();
+        let ctx = SampleContext {
+            interval: actual_interval,
+        };
 
-        AllMetrics {
+        let mut metrics = AllMetrics {
             timestamp_ms,
             interval_ms: actual_interval.as_millis() as u64,
-            memory,
-            cpu_clusters,
-            gpu,
-            ane,
-            disks,
-            system,
-        }
-    }
-
-    fn collect_ioreport_metrics(
-        &mut self,
-    ) -> (Vec<CpuClusterMetrics>, GpuMetrics, AneMetrics, SystemMetrics) {
-        let mut cpu_clusters = Vec::new();
-        let mut gpu = GpuMetrics::default();
-        let mut ane = AneMetrics::default();
-        let mut system = SystemMetrics::default();
-
-        // Get samples from IOReport
-        if let Some(ref mut ioreport) = self.ioreport {
-            let samples = ioreport.get_sample();
-
-            // Aggregate by cluster/component
-            let mut ecpu_residency = 0i64;
-            let mut ecpu_total = 0i64;
-            let mut pcpu_residency = 0i64;
-            let mut pcpu_total = 0i64;
-
-            for sample in &samples {
-                match sample.group.as_str() {
-                    "CPU Stats" => {
-                        if sample.channel.contains("ECPU") || sample.channel.contains("E-Cluster")
-                        {
-                            if sample.subgroup.contains("Performance States") {
-                                ecpu_total += sample.value.max(0);
-                                if !sample.channel.contains("IDLE") {
-                                    ecpu_residency += sample.value.max(0);
-                                }
-                            }
-                        } else if sample.channel.contains("PCPU")
-                            || sample.channel.contains("P-Cluster")
-                        {
-                            if sample.subgroup.contains("Performance States") {
-                                pcpu_total += sample.value.max(0);
-                                if !sample.channel.contains("IDLE") {
-                                    pcpu_residency += sample.value.max(0);
-                                }
-                            }
-                        }
-                    }
-                    "Energy Model" => {
-                        let power_mw = sample.value as f64 / 1000.0; // Typically in uW or nW
-                        let power_w = power_mw / 1000.0;
-
-                        if sample.channel.contains("CPU") {
-                            system.cpu_power_watts += power_w;
-                        } else if sample.channel.contains("GPU") {
-                            system.gpu_power_watts += power_w;
-                            gpu.power_watts = power_w;
-                        } else if sample.channel.contains("ANE") {
-                            system.ane_power_watts += power_w;
-                            ane.power_watts = power_w;
-                        } else if sample.channel.contains("DRAM") {
-                            system.dram_power_watts += power_w;
-                        }
-                    }
-                    "GPU Stats" => {
-                        // GPU frequency/utilization
-                        if sample.subgroup.contains("Performance States") {
-                            // GPU utilization from residency
-                        }
-                    }
-                    _ => {}
-                }
+            ..Default::default()
+        };
+
+        // Each source samples into scratch space; only the slices it declares are kept
+        for slot in &mut self.sources {
+            if let Some(partial) = slot.sample(&ctx) {
+                merge(&mut metrics, partial, slot.source.capabilities());
             }
+        }
+        metrics.sources = self.health();
+
+        // Figures that combine what several sources reported
+        let system = &mut metrics.system;
+        system.unattributed_power_watts = system
+            .wall_power_watts
+            .filter(|_| system.total_power_watts > 0.0)
+            .map(|wall| wall - system.total_power_watts);
+        metrics.thermal.groups = SensorGroup::from_sensors(&metrics.thermal.sensors);
+        self.thermal_history.record(now, &mut metrics.thermal);
+
+        // Fallback: create default clusters based on sysctl info
+        if metrics.cpu_clusters.is_empty() {
+            metrics.cpu_clusters = self.placeholder_clusters();
+        }
+
+        metrics
+    }
+
+    fn placeholder_clusters(&self) -> Vec<CpuClusterMetrics> {
+        let mut clusters = Vec::new();
+        if self.sysctl_info.cpu_cores_eff > 0 {
+            clusters.push(CpuClusterMetrics {
+                name: "E-Cluster".to_string(),
+                core_type: CoreType::Efficiency,
+                idle_pct: 100.0,
+                ..Default::default()
+            });
+        }
+        if self.sysctl_info.cpu_cores_perf > 0 {
+            clusters.push(CpuClusterMetrics {
+                name: "P-Cluster".to_string(),
+                core_type: CoreType::Performance,
+                idle_pct: 100.0,
+                ..Default::default()
+            });
+        }
+        clusters
+    }
+
+    pub fn cpu_brand(&self) -> &str {
+        &self.sysctl_info.cpu_brand
+    }
 
-            // Calculate CPU cluster metrics
-            if ecpu_total > 0 {
-                let ecpu_active = (ecpu_residency as f64 / ecpu_total as f64 * 100.0).min(100.0);
-                cpu_clusters.push(CpuClusterMetrics {
-                    name: "E-Cluster".to_string(),
-                    freq_mhz: 0, // Would need DVFS data
-                    freq_max_mhz: 0,
-                    active_pct: ecpu_active,
-                    idle_pct: 100.0 - ecpu_active,
-                    power_watts: 0.0, // Part of system.cpu_power_watts
-                });
+    /// Host description for a `MetricsRecord`, with the current uptime.
+    pub fn host_metadata(&self) -> HostMetadata {
+        HostMetadata {
+            hostname: sources::hostname(),
+            os_version: sources::os_version(),
+            chip: self.sysctl_info.cpu_brand.clone(),
+            uptime_secs: sources::uptime_secs(),
+        }
+    }
+
+    /// Wrap a sample in the versioned envelope used for JSON output.
+    pub fn record(&self, metrics: AllMetrics) -> MetricsRecord {
+        MetricsRecord {
+            schema_version: SCHEMA_VERSION,
+            host: self.host_metadata(),
+            metrics,
+        }
+    }
+}
+
+/// Recent sensor group maxima, for `SensorGroup::peak_c`.
+#[derive(Default)]
+struct ThermalHistory {
+    /// Each sample's (group, max_c) pairs, oldest first
+    readings: VecDeque<(Instant, Vec<(String, f64)>)>,
+}
+
+impl ThermalHistory {
+    /// Add this sample's group maxima and fill in each group's peak over the
+    /// window.
+    fn record(&mut self, now: Instant, thermal: &mut ThermalMetrics) {
+        let window = Duration::from_secs(THERMAL_MAX_WINDOW_SECS);
+        while let Some(&(at, _)) = self.readings.front() {
+            if now.duration_since(at) <= window {
+                break;
             }
+            self.readings.pop_front();
+        }
+        self.readings.push_back((
+            now,
+            thermal
+                .groups
+                .iter()
+                .map(|group| (group.name.clone(), group.max_c))
+                .collect(),
+        ));
+
+        for group in &mut thermal.groups {
+            group.peak_c = self
+                .readings
+                .iter()
+                .flat_map(|(_, maxima)| maxima)
+                .filter(|(name, _)| *name == group.name)
+                .map(|&(_, max_c)| max_c)
+                .fold(group.max_c, f64::max);
+        }
+    }
+}
+
+/// Configures the sources and interval of a `MetricsCollector`.
+///
+/// ```
+/// use bustop::sources::{Capability, MetricSource, SampleContext, SimulatedSource};
+/// use bustop::types::AllMetrics;
+/// use bustop::{Error, MetricsCollector};
+/// use std::time::Duration;
+///
+/// struct FixedMemory;
+///
+/// impl MetricSource for FixedMemory {
+///     fn name(&self) -> &'static str {
+///         "fixed-memory"
+///     }
+///
+///     fn capabilities(&self) -> &'static [Capability] {
+///         &[Capability::Memory]
+///     }
+///
+///     fn sample(&mut self, _ctx: &SampleContext, out: &mut AllMetrics) -> Result<(), Error> {
+///         out.memory.used_bytes = 42;
+///         Ok(())
+///     }
+/// }
+///
+/// let mut collector = MetricsCollector::builder()
+///     .without_platform_sources()
+///     .host_info(SimulatedSource::host_info())
+///     .source(Box::new(FixedMemory))
+///     .interval(Duration::from_millis(250))
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(collector.source_names(), ["fixed-memory"]);
+/// assert_eq!(collector.collect().memory.used_bytes, 42);
+/// ```
+pub struct CollectorBuilder {
+    interval: Duration,
+    platform_sources: bool,
+    host_info: Option<SysctlInfo>,
+    sources: Vec<Box<dyn MetricSource>>,
+    disabled: Vec<String>,
+    strict: bool,
+}
+
+impl CollectorBuilder {
+    pub fn new() -> Self {
+        Self {
+            interval: DEFAULT_INTERVAL,
+            platform_sources: true,
+            host_info: None,
+            sources: Vec::new(),
+            disabled: Vec::new(),
+            strict: false,
+        }
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Start from an empty registry instead of the platform defaults.
+    pub fn without_platform_sources(mut self) -> Self {
+        self.platform_sources = false;
+        self
+    }
+
+    /// Use the given host description instead of querying the OS.
+    pub fn host_info(mut self, host_info: SysctlInfo) -> Self {
+        self.host_info = Some(host_info);
+        self
+    }
+
+    /// Add a source, replacing any registered source with the same name.
+    pub fn source(mut self, source: Box<dyn MetricSource>) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// Drop a source by name. `build` fails if no source has that name.
+    pub fn disable_source(mut self, name: impl Into<String>) -> Self {
+        self.disabled.push(name.into());
+        self
+    }
+
+    /// Make `build` fail if a required source can't be initialized, instead
+    /// of reporting it as failed. Sources added with `source` are required,
+    /// as are platform sources that aren't `optional`. Only initialization
+    /// is checked: a source whose samples fail later is reported as degraded
+    /// in `AllMetrics::sources` and keeps being retried.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
 
-            if pcpu_total > 0 {
-                let pcpu_active = (pcpu_residency as f64 / pcpu_total as f64 * 100.0).min(100.0);
-                cpu_clusters.push(CpuClusterMetrics {
-                    name: "P-Cluster".to_string(),
-                    freq_mhz: 0,
-                    freq_max_mhz: 0,
-                    active_pct: pcpu_active,
-                    idle_pct: 100.0 - pcpu_active,
-                    power_watts: 0.0,
-                });
+    pub fn build(self) -> Result<MetricsCollector, Error> {
+        let host_info = match self.host_info {
+            Some(info) => info,
+            None => SysctlInfo::new().map_err(Error::HostInfo)?,
+        };
+
+        let mut registry = if self.platform_sources {
+            SourceRegistry::platform_default(&host_info)
+        } else {
+            SourceRegistry::new()
+        };
+        let requested: Vec<&'static str> = self.sources.iter().map(|s| s.name()).collect();
+        for source in self.sources {
+            registry.register(source);
+        }
+        for name in &self.disabled {
+            registry.disable(name)?;
+        }
+
+        let mut collector = MetricsCollector::new(host_info, registry, self.interval);
+        if self.strict {
+            if let Some(error) = collector.take_required_init_error(&requested) {
+                return Err(error);
             }
+        }
+        Ok(collector)
+    }
+}
+
+impl Default for CollectorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A registered source together with its health bookkeeping.
+struct SourceSlot {
+    source: Box<dyn MetricSource>,
+    init_error: Option<Error>,
+    health: SourceHealth,
+}
 
-            system.total_power_watts = system.cpu_power_watts
-                + system.gpu_power_watts
-                + system.ane_power_watts
-                + system.dram_power_watts;
+impl SourceSlot {
+    fn init(mut source: Box<dyn MetricSource>) -> Self {
+        let init_error = source.init().err();
+        let health = SourceHealth {
+            name: source.name().to_string(),
+            status: match init_error {
+                Some(_) => SourceStatus::Failed,
+                None => SourceStatus::Available,
+            },
+            last_error: init_error.as_ref().map(|e| e.to_string()),
+            consecutive_failures: 0,
+        };
+
+        Self {
+            source,
+            init_error,
+            health,
         }
+    }
 
-        // Fallback: create default clusters based on sysctl info
-        if cpu_clusters.is_empty() {
-            if self.sysctl_info.cpu_cores_eff > 0 {
-                cpu_clusters.push(CpuClusterMetrics {
-                    name: "E-Cluster".to_string(),
-                    freq_mhz: 0,
-                    freq_max_mhz: 0,
-                    active_pct: 0.0,
-                    idle_pct: 100.0,
-                    power_watts: 0.0,
-                });
+    /// Sample the source into scratch space, updating its health. Returns
+    /// `None` if the source failed to initialize or to sample.
+    fn sample(&mut self, ctx: &SampleContext) -> Option<AllMetrics> {
+        if self.init_error.is_some() {
+            return None;
+        }
+
+        let mut partial = AllMetrics::default();
+        match self.source.sample(ctx, &mut partial) {
+            Ok(()) => {
+                self.health.status = SourceStatus::Available;
+                self.health.consecutive_failures = 0;
+                Some(partial)
+            }
+            Err(e) => {
+                self.health.status = SourceStatus::Degraded;
+                self.health.last_error = Some(e.to_string());
+                self.health.consecutive_failures += 1;
+                None
+            }
+        }
+    }
+}
+
+fn merge(dst: &mut AllMetrics, src: AllMetrics, capabilities: &[Capability]) {
+    for capability in capabilities {
+        match capability {
+            Capability::Memory => dst.memory = src.memory.clone(),
+            Capability::CpuClusters => {
+                dst.cpu_clusters.extend(src.cpu_clusters.iter().cloned());
+                dst.cpu_cores.extend(src.cpu_cores.iter().cloned());
+            }
+            Capability::Gpu => dst.gpu = src.gpu.clone(),
+            Capability::Ane => dst.ane = src.ane.clone(),
+            Capability::Disks => dst.disks.extend(src.disks.iter().cloned()),
+            Capability::Bandwidth => dst.memory_bandwidth = src.memory_bandwidth.clone(),
+            Capability::Power => {
+                dst.system.total_power_watts = src.system.total_power_watts;
+                dst.system.cpu_power_watts = src.system.cpu_power_watts;
+                dst.system.gpu_power_watts = src.system.gpu_power_watts;
+                dst.system.ane_power_watts = src.system.ane_power_watts;
+                dst.system.dram_power_watts = src.system.dram_power_watts;
+                dst.system.total_energy_joules = src.system.total_energy_joules;
+                dst.system.cpu_energy_joules = src.system.cpu_energy_joules;
+                dst.system.gpu_energy_joules = src.system.gpu_energy_joules;
+                dst.system.ane_energy_joules = src.system.ane_energy_joules;
+                dst.system.dram_energy_joules = src.system.dram_energy_joules;
+                dst.power_tree = src.power_tree.clone();
+            }
+            Capability::WallPower => {
+                dst.system.wall_power_watts = src.system.wall_power_watts;
+                dst.system.adapter_power_watts = src.system.adapter_power_watts;
+                dst.system.battery_power_watts = src.system.battery_power_watts;
+            }
+            Capability::Thermal => {
+                dst.system.thermal_pressure = src.system.thermal_pressure;
+                dst.thermal.pressure = src.system.thermal_pressure;
+            }
+            Capability::Sensors => {
+                dst.thermal.sensors = src.thermal.sensors.clone();
+                dst.thermal.fans = src.thermal.fans.clone();
+            }
+            Capability::RawChannels => dst.raw_channels.extend(src.raw_channels.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::SimulatedSource;
+
+    /// Reports 42 used bytes, except for the samples listed in `failing`
+    /// (counting from 0). Fails `init` if `init_fails`.
+    struct Mock {
+        name: &'static str,
+        optional: bool,
+        init_fails: bool,
+        failing: &'static [u64],
+        samples: u64,
+    }
+
+    impl Mock {
+        fn new(name: &'static str) -> Self {
+            Self {
+                name,
+                optional: false,
+                init_fails: false,
+                failing: &[],
+                samples: 0,
             }
-            if self.sysctl_info.cpu_cores_perf > 0 {
-                cpu_clusters.push(CpuClusterMetrics {
-                    name: "P-Cluster".to_string(),
-                    freq_mhz: 0,
-                    freq_max_mhz: 0,
-                    active_pct: 0.0,
-                    idle_pct: 100.0,
-                    power_watts: 0.0,
-                });
+        }
+    }
+
+    impl MetricSource for Mock {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn init(&mut self) -> Result<(), Error> {
+            if self.init_fails {
+                return Err(Error::Unavailable("no such device".into()));
             }
+            Ok(())
+        }
+
+        fn optional(&self) -> bool {
+            self.optional
+        }
+
+        fn capabilities(&self) -> &'static [Capability] {
+            &[Capability::Memory]
         }
 
-        // Try to get thermal pressure
-        system.thermal_pressure = self.get_thermal_pressure();
+        fn sample(&mut self, _ctx: &SampleContext, out: &mut AllMetrics) -> Result<(), Error> {
+            let idx = self.samples;
+            self.samples += 1;
+            if self.failing.contains(&idx) {
+                return Err(Error::Read(format!("sample {} failed", idx)));
+            }
+            out.memory.used_bytes = 42;
+            Ok(())
+        }
+    }
 
-        (cpu_clusters, gpu, ane, system)
+    fn builder() -> CollectorBuilder {
+        MetricsCollector::builder()
+            .without_platform_sources()
+            .host_info(SimulatedSource::host_info())
     }
 
-    fn get_thermal_pressure(&self) -> ThermalPressure {
-        // Try to read from sysctl
-        unsafe {
-            let name = std::ffi::CString::new("kern.thermalpressure").unwrap();
-            let mut level: i32 = 0;
-            let mut size = std::mem::size_of::<i32>();
+    #[test]
+    fn health_goes_degraded_and_recovers() {
+        let mock = Mock {
+            failing: &[1, 2],
+            ..Mock::new("mock")
+        };
+        let mut collector = builder().source(Box::new(mock)).build().unwrap();
 
-            let result = libc::sysctlbyname(
-                name.as_ptr(),
-                &mut level as *mut i32 as *mut libc::c_void,
-                &mut size,
-                std::ptr::null_mut(),
-                0,
+        let expected = [
+            (SourceStatus::Available, 0, 42),
+            (SourceStatus::Degraded, 1, 0),
+            (SourceStatus::Degraded, 2, 0),
+            (SourceStatus::Available, 0, 42),
+        ];
+        for (idx, (status, failures, used_bytes)) in expected.into_iter().enumerate() {
+            let metrics = collector.collect();
+            let health = &metrics.sources[0];
+
+            assert_eq!(health.status, status, "sample {}", idx);
+            assert_eq!(health.consecutive_failures, failures, "sample {}", idx);
+            assert_eq!(metrics.memory.used_bytes, used_bytes, "sample {}", idx);
+        }
+        // The last error stays visible after recovery
+        assert_eq!(
+            collector.health()[0].last_error.as_deref(),
+            Some("sample 2 failed")
+        );
+    }
+
+    #[test]
+    fn failed_init_is_never_sampled() {
+        let mock = Mock {
+            init_fails: true,
+            ..Mock::new("mock")
+        };
+        let mut collector = builder().source(Box::new(mock)).build().unwrap();
+
+        assert!(collector.source_names().is_empty());
+        for _ in 0..2 {
+            let metrics = collector.collect();
+            assert_eq!(metrics.memory.used_bytes, 0);
+            assert_eq!(metrics.sources[0].status, SourceStatus::Failed);
+            assert_eq!(metrics.sources[0].consecutive_failures, 0);
+            assert_eq!(
+                metrics.sources[0].last_error.as_deref(),
+                Some("no such device")
             );
+        }
+    }
+
+    #[test]
+    fn strict_rejects_requested_sources_that_fail_to_init() {
+        for optional in [false, true] {
+            let mock = Mock {
+                optional,
+                init_fails: true,
+                ..Mock::new("mock")
+            };
+            let result = builder()
+                .source(Box::new(Mock::new("healthy")))
+                .source(Box::new(mock))
+                .strict(true)
+                .build();
 
-            if result == 0 {
-                match level {
-                    0 => ThermalPressure::Nominal,
-                    1 => ThermalPressure::Moderate,
-                    2 => ThermalPressure::Heavy,
-                    3 => ThermalPressure::Critical,
-                    4 => ThermalPressure::Sleeping,
-                    _ => ThermalPressure::Nominal,
+            match result {
+                Err(Error::SourceInit { name, error }) => {
+                    assert_eq!(name, "mock");
+                    assert!(matches!(*error, Error::Unavailable(_)));
                 }
-            } else {
-                ThermalPressure::Nominal
+                Err(e) => panic!("unexpected error: {}", e),
+                Ok(_) => panic!("strict build succeeded with optional = {}", optional),
             }
         }
     }
 
-    pub fn cpu_brand(&self) -> &str {
-        &self.sysctl_info.cpu_brand
+    #[test]
+    fn strict_tolerates_optional_platform_sources() {
+        let mut registry = SourceRegistry::new();
+        registry.register(Box::new(Mock::new("healthy")));
+        registry.register(Box::new(Mock {
+            optional: true,
+            init_fails: true,
+            ..Mock::new("optional")
+        }));
+        registry.register(Box::new(Mock {
+            init_fails: true,
+            ..Mock::new("required")
+        }));
+        let mut collector =
+            MetricsCollector::new(SimulatedSource::host_info(), registry, DEFAULT_INTERVAL);
+
+        match collector.take_required_init_error(&[]) {
+            Some(Error::SourceInit { name, .. }) => assert_eq!(name, "required"),
+            other => panic!("expected the required source, got {:?}", other),
+        }
+        assert!(collector.take_required_init_error(&[]).is_none());
+    }
+
+    #[test]
+    fn strict_ignores_sample_failures() {
+        let mock = Mock {
+            failing: &[0],
+            ..Mock::new("mock")
+        };
+        let mut collector = builder()
+            .source(Box::new(mock))
+            .strict(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            collector.collect().sources[0].status,
+            SourceStatus::Degraded
+        );
     }
 }
@@ -0,0 +1,208 @@
+use bustop::types::{AllMetrics, HostMetadata, MetricsRecord, SCHEMA_VERSION};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Bumped whenever the recording layout changes incompatibly.
+pub const RECORDING_VERSION: u32 = 1;
+
+/// First line of a recording. Every following line is one `MetricsRecord`,
+/// exactly as printed by `--json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingHeader {
+    pub version: u32,
+    pub bustop_version: String,
+    pub host: String,
+    pub cpu_brand: String,
+    pub interval_ms: u64,
+}
+
+pub struct Recorder {
+    writer: BufWriter<File>,
+}
+
+impl Recorder {
+    pub fn create(path: &Path, header: &RecordingHeader) -> Result<Self, String> {
+        let file = File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut recorder = Self {
+            writer: BufWriter::new(file),
+        };
+        recorder.write_line(header)?;
+        Ok(recorder)
+    }
+
+    /// Append a sample. Flushed immediately so an interrupted run keeps
+    /// everything recorded so far.
+    pub fn write(&mut self, record: &MetricsRecord) -> Result<(), String> {
+        self.write_line(record)
+    }
+
+    fn write_line<T: Serialize>(&mut self, value: &T) -> Result<(), String> {
+        serde_json::to_writer(&mut self.writer, value).map_err(|e| e.to_string())?;
+        self.writer.write_all(b"\n").map_err(|e| e.to_string())?;
+        self.writer.flush().map_err(|e| e.to_string())
+    }
+}
+
+pub struct Recording {
+    pub header: RecordingHeader,
+    pub samples: Vec<MetricsRecord>,
+}
+
+impl Recording {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::read(BufReader::new(file), &path.display().to_string())
+    }
+
+    /// Parse a recording from `reader`; `name` prefixes error messages.
+    fn read(reader: impl BufRead, name: &str) -> Result<Self, String> {
+        let mut lines = reader.lines();
+
+        let header_line = lines
+            .next()
+            .ok_or_else(|| format!("{}: empty recording", name))?
+            .map_err(|e| e.to_string())?;
+        let header: RecordingHeader = serde_json::from_str(&header_line)
+            .map_err(|e| format!("{}: invalid header: {}", name, e))?;
+        if header.version != RECORDING_VERSION {
+            return Err(format!(
+                "{}: unsupported recording version {} (expected {})",
+                name, header.version, RECORDING_VERSION
+            ));
+        }
+
+        let mut samples = Vec::new();
+        for (idx, line) in lines.enumerate() {
+            let line = line.map_err(|e| e.to_string())?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let sample =
+                parse_sample(&line, &header).map_err(|e| format!("{}:{}: {}", name, idx + 2, e))?;
+            samples.push(sample);
+        }
+
+        Ok(Self { header, samples })
+    }
+}
+
+/// Samples recorded before the JSON envelope existed are bare `AllMetrics`;
+/// wrap them with what the header knows about the host.
+fn parse_sample(line: &str, header: &RecordingHeader) -> Result<MetricsRecord, String> {
+    if let Ok(record) = serde_json::from_str::<MetricsRecord>(line) {
+        return Ok(record);
+    }
+
+    let metrics: AllMetrics = serde_json::from_str(line).map_err(|e| e.to_string())?;
+    Ok(MetricsRecord {
+        schema_version: SCHEMA_VERSION,
+        host: HostMetadata {
+            hostname: header.host.clone(),
+            chip: header.cpu_brand.clone(),
+            ..Default::default()
+        },
+        metrics,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header() -> RecordingHeader {
+        RecordingHeader {
+            version: RECORDING_VERSION,
+            bustop_version: "0.1.0".to_string(),
+            host: "test-host".to_string(),
+            cpu_brand: "Apple M2".to_string(),
+            interval_ms: 250,
+        }
+    }
+
+    #[test]
+    fn round_trips_through_a_file() {
+        let path = std::env::temp_dir().join(format!("bustop-record-{}.jsonl", std::process::id()));
+        let mut record = MetricsRecord {
+            schema_version: SCHEMA_VERSION,
+            ..Default::default()
+        };
+        record.host.hostname = "test-host".to_string();
+
+        let mut recorder = Recorder::create(&path, &header()).unwrap();
+        for timestamp_ms in [1000, 1250] {
+            record.metrics.timestamp_ms = timestamp_ms;
+            record.metrics.memory.used_bytes = timestamp_ms * 2;
+            recorder.write(&record).unwrap();
+        }
+        drop(recorder);
+        let recording = Recording::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        let recording = recording.unwrap();
+
+        assert_eq!(recording.header.host, "test-host");
+        assert_eq!(recording.header.interval_ms, 250);
+        assert_eq!(recording.samples.len(), 2);
+        assert_eq!(recording.samples[1].metrics.timestamp_ms, 1250);
+        assert_eq!(recording.samples[1].metrics.memory.used_bytes, 2500);
+        assert_eq!(recording.samples[1].host.hostname, "test-host");
+    }
+
+    #[test]
+    fn rejects_other_recording_versions() {
+        let mut header = header();
+        header.version = RECORDING_VERSION + 1;
+        let text = serde_json::to_string(&header).unwrap() + "\n";
+
+        let err = Recording::read(text.as_bytes(), "future.jsonl")
+            .err()
+            .unwrap();
+        assert!(err.contains("unsupported recording version"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_empty_and_malformed_recordings() {
+        assert!(Recording::read(&b""[..], "empty.jsonl").is_err());
+        assert!(Recording::read(&b"not json\n"[..], "bad.jsonl").is_err());
+
+        let text = serde_json::to_string(&header()).unwrap() + "\n{\"timestamp_ms\": \"x\"}\n";
+        let err = Recording::read(text.as_bytes(), "bad.jsonl").err().unwrap();
+        assert!(err.starts_with("bad.jsonl:2:"), "{}", err);
+    }
+
+    #[test]
+    fn wraps_bare_metrics_lines() {
+        // Written before samples carried `schema_version` and `host`
+        let recording = Recording::read(
+            include_str!("../tests/fixtures/recordings/legacy_bare.jsonl").as_bytes(),
+            "legacy_bare.jsonl",
+        )
+        .unwrap();
+
+        assert_eq!(recording.samples.len(), 2);
+        let sample = &recording.samples[0];
+        assert_eq!(sample.schema_version, SCHEMA_VERSION);
+        assert_eq!(sample.host.hostname, recording.header.host);
+        assert_eq!(sample.host.chip, "bustop simulator");
+        assert!(sample.metrics.memory.total_bytes > 0);
+        assert_eq!(sample.metrics.cpu_clusters.len(), 2);
+    }
+
+    #[test]
+    fn loads_schema_1_thermal_samples() {
+        // Written while `thermal` still had cpu/gpu/soc temperature fields
+        let recording = Recording::read(
+            include_str!("../tests/fixtures/recordings/schema1_thermal.jsonl").as_bytes(),
+            "schema1_thermal.jsonl",
+        )
+        .unwrap();
+
+        assert_eq!(recording.samples.len(), 2);
+        let sample = &recording.samples[0];
+        assert_eq!(sample.schema_version, 1);
+        assert!(sample.metrics.thermal.sensors.is_empty());
+        assert!(sample.metrics.thermal.groups.is_empty());
+        assert_eq!(sample.metrics.thermal.fans.len(), 1);
+    }
+}
@@ -0,0 +1,699 @@
+use crate::error::Error;
+use crate::sources::{procfs, Capability, MetricSource, SampleContext};
+use crate::types::{AllMetrics, CoreType, CpuClusterMetrics, CpuCoreMetrics, FreqResidency};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+const SYSFS_CPU: &str = "/sys/devices/system/cpu";
+
+// Above this many cluster_ids per core type the ids describe L2 modules or
+// core pairs rather than clusters worth a row each, so the type is kept whole.
+// Only consulted on hybrid parts; with a single core type, cluster_id is
+// usually one id per core or per L2 and never a useful split.
+const MAX_CLUSTERS_PER_TYPE: usize = 4;
+
+/// Where a CPU sits in the topology, as read from sysfs.
+#[derive(Debug, Clone, Copy)]
+struct CpuTopology {
+    cpu: u32,
+    /// Relative performance tier; higher is faster
+    tier: u64,
+    cluster_id: i64,
+}
+
+#[derive(Debug, Clone)]
+struct CpuCluster {
+    name: String,
+    core_type: CoreType,
+    cpus: Vec<u32>,
+    /// cpufreq policy directories covering these CPUs
+    policies: Vec<PathBuf>,
+}
+
+/// Cumulative time per frequency (kHz -> 10ms units) from `stats/time_in_state`.
+type TimeInState = BTreeMap<u32, u64>;
+
+/// One reading of a cpufreq policy directory.
+#[derive(Debug, Clone, Default)]
+struct PolicySample {
+    /// Empty without cpufreq stats
+    time_in_state: TimeInState,
+    cur_khz: Option<u64>,
+    max_khz: Option<u64>,
+}
+
+impl PolicySample {
+    fn read(policy: &Path) -> Self {
+        PolicySample {
+            time_in_state: procfs::read(policy.join("stats/time_in_state"))
+                .map(|text| parse_time_in_state(&text))
+                .unwrap_or_default(),
+            cur_khz: procfs::read_u64(policy.join("scaling_cur_freq")),
+            max_khz: procfs::read_u64(policy.join("scaling_max_freq")),
+        }
+    }
+}
+
+/// Frequencies of one or more cpufreq policies over the last interval.
+#[derive(Debug, Clone, Default)]
+struct PolicyFrequency {
+    /// Residency-weighted average, or the current frequency without
+    /// time_in_state
+    avg_mhz: u32,
+    /// Average `scaling_cur_freq` at the end of the interval
+    cur_mhz: u32,
+    max_mhz: u32,
+    residency: Vec<FreqResidency>,
+}
+
+/// Cumulative jiffies for one CPU from /proc/stat.
+#[derive(Debug, Clone, Copy, Default)]
+struct CpuTimes {
+    busy: u64,
+    total: u64,
+}
+
+/// Per-cluster active/idle residency from /proc/stat and frequency from
+/// cpufreq on Linux.
+///
+/// CPUs are grouped by core type (Intel hybrid PMU lists, otherwise
+/// `cpu_capacity`) and, on hybrid parts, by `cluster_id`.
+#[derive(Default)]
+pub struct CpuStats {
+    clusters: Vec<CpuCluster>,
+    prev_times: HashMap<u32, CpuTimes>,
+    prev_time_in_state: HashMap<PathBuf, TimeInState>,
+    /// The cpufreq policy governing each CPU
+    core_policies: HashMap<u32, PathBuf>,
+}
+
+impl CpuStats {
+    pub fn new() -> Self {
+        Self {
+            clusters: Vec::new(),
+            prev_times: HashMap::new(),
+            prev_time_in_state: HashMap::new(),
+            core_policies: HashMap::new(),
+        }
+    }
+
+    /// Average, current and max frequency and residency histogram across
+    /// a set of cpufreq policies over the last interval.
+    fn policy_frequency(
+        &self,
+        policies: &[PathBuf],
+        samples: &HashMap<PathBuf, PolicySample>,
+    ) -> PolicyFrequency {
+        let samples: Vec<(&PathBuf, &PolicySample)> = policies
+            .iter()
+            .filter_map(|policy| Some((policy, samples.get(policy)?)))
+            .collect();
+
+        let mut deltas = TimeInState::new();
+        for (policy, sample) in &samples {
+            let Some(prev) = self.prev_time_in_state.get(*policy) else {
+                continue;
+            };
+            for (&khz, &time) in &sample.time_in_state {
+                let prev_time = prev.get(&khz).copied().unwrap_or(0);
+                *deltas.entry(khz).or_default() += time.saturating_sub(prev_time);
+            }
+        }
+
+        let current: Vec<u64> = samples.iter().filter_map(|(_, s)| s.cur_khz).collect();
+        let cur_khz = if current.is_empty() {
+            0
+        } else {
+            current.iter().sum::<u64>() / current.len() as u64
+        };
+        let max_khz = samples
+            .iter()
+            .filter_map(|(_, s)| s.max_khz)
+            .max()
+            .unwrap_or(0);
+
+        // No time_in_state (or no time elapsed): the current frequency is
+        // the best estimate of the average
+        let (avg_mhz, residency) =
+            residency_frequency(&deltas).unwrap_or(((cur_khz / 1000) as u32, Vec::new()));
+
+        PolicyFrequency {
+            avg_mhz,
+            cur_mhz: (cur_khz / 1000) as u32,
+            max_mhz: (max_khz / 1000) as u32,
+            residency,
+        }
+    }
+
+    /// A core's frequency: its own policy's when the cluster has several
+    /// (per-core DVFS, intel_pstate), otherwise the cluster's.
+    fn core_frequency(
+        &self,
+        cluster: &CpuCluster,
+        cpu: u32,
+        samples: &HashMap<PathBuf, PolicySample>,
+        cluster_mhz: u32,
+    ) -> u32 {
+        match self.core_policies.get(&cpu) {
+            Some(policy) if cluster.policies.len() > 1 => {
+                self.policy_frequency(std::slice::from_ref(policy), samples)
+                    .avg_mhz
+            }
+            _ => cluster_mhz,
+        }
+    }
+}
+
+/// Residency-weighted average frequency in MHz and the residency histogram
+/// for time spent per frequency; `None` if no time was spent at all.
+fn residency_frequency(deltas: &TimeInState) -> Option<(u32, Vec<FreqResidency>)> {
+    let total: u64 = deltas.values().sum();
+    if total == 0 {
+        return None;
+    }
+
+    let weighted_khz: f64 = deltas
+        .iter()
+        .map(|(&khz, &time)| khz as f64 * time as f64)
+        .sum::<f64>()
+        / total as f64;
+    let residency = deltas
+        .iter()
+        .map(|(&khz, &time)| FreqResidency {
+            freq_mhz: khz / 1000,
+            residency_pct: time as f64 / total as f64 * 100.0,
+        })
+        .collect();
+
+    Some(((weighted_khz / 1000.0).round() as u32, residency))
+}
+
+impl MetricSource for CpuStats {
+    fn name(&self) -> &'static str {
+        "cpu"
+    }
+
+    fn init(&mut self) -> Result<(), Error> {
+        let online =
+            procfs::read(Path::new(SYSFS_CPU).join("online")).map_err(Error::Unavailable)?;
+        let topology = read_topology(&procfs::parse_cpu_list(&online));
+        if topology.is_empty() {
+            return Err(Error::Unavailable("no online CPUs found".into()));
+        }
+        self.clusters = group_clusters(&topology);
+
+        let policies = read_policies();
+        for cluster in &mut self.clusters {
+            cluster.policies = policies
+                .iter()
+                .filter(|(_, cpus)| cpus.iter().any(|cpu| cluster.cpus.contains(cpu)))
+                .map(|(path, _)| path.clone())
+                .collect();
+        }
+        self.core_policies = policies
+            .iter()
+            .flat_map(|(path, cpus)| cpus.iter().map(move |&cpu| (cpu, path.clone())))
+            .collect();
+        Ok(())
+    }
+
+    fn capabilities(&self) -> &'static [Capability] {
+        &[Capability::CpuClusters]
+    }
+
+    fn sample(&mut self, _ctx: &SampleContext, out: &mut AllMetrics) -> Result<(), Error> {
+        let text = procfs::read("/proc/stat").map_err(Error::Read)?;
+        let times = parse_proc_stat(&text);
+        let policies: HashMap<PathBuf, PolicySample> = self
+            .clusters
+            .iter()
+            .flat_map(|cluster| &cluster.policies)
+            .map(|policy| (policy.clone(), PolicySample::read(policy)))
+            .collect();
+
+        // Like IOReport deltas, nothing to report until a second sample
+        if !self.prev_times.is_empty() {
+            for cluster in &self.clusters {
+                let (busy, total) = cluster
+                    .cpus
+                    .iter()
+                    .filter_map(|cpu| Some((times.get(cpu)?, self.prev_times.get(cpu)?)))
+                    .fold((0u64, 0u64), |(busy, total), (cur, prev)| {
+                        (
+                            busy + cur.busy.saturating_sub(prev.busy),
+                            total + cur.total.saturating_sub(prev.total),
+                        )
+                    });
+
+                if total == 0 {
+                    continue;
+                }
+
+                let active = (busy as f64 / total as f64 * 100.0).min(100.0);
+                let freq = self.policy_frequency(&cluster.policies, &policies);
+
+                for &cpu in &cluster.cpus {
+                    let (Some(cur), Some(prev)) = (times.get(&cpu), self.prev_times.get(&cpu))
+                    else {
+                        continue;
+                    };
+                    let total = cur.total.saturating_sub(prev.total);
+                    if total == 0 {
+                        continue;
+                    }
+                    let busy = cur.busy.saturating_sub(prev.busy);
+                    out.cpu_cores.push(CpuCoreMetrics {
+                        id: cpu,
+                        cluster: cluster.name.clone(),
+                        active_pct: (busy as f64 / total as f64 * 100.0).min(100.0),
+                        freq_mhz: self.core_frequency(cluster, cpu, &policies, freq.avg_mhz),
+                    });
+                }
+
+                out.cpu_clusters.push(CpuClusterMetrics {
+                    name: cluster.name.clone(),
+                    die: 0,
+                    core_type: cluster.core_type,
+                    freq_mhz: freq.avg_mhz,
+                    freq_cur_mhz: freq.cur_mhz,
+                    freq_max_mhz: freq.max_mhz,
+                    active_pct: active,
+                    idle_pct: 100.0 - active,
+                    power_watts: 0.0,
+                    freq_residency: freq.residency,
+                });
+            }
+        }
+
+        out.cpu_cores.sort_by_key(|core| core.id);
+        self.prev_times = times;
+        self.prev_time_in_state = policies
+            .into_iter()
+            .map(|(policy, sample)| (policy, sample.time_in_state))
+            .collect();
+        Ok(())
+    }
+}
+
+fn read_topology(cpus: &[u32]) -> Vec<CpuTopology> {
+    // Intel hybrid parts list their P/E cores under separate PMUs
+    let atom_cpus: BTreeSet<u32> = procfs::read("/sys/devices/cpu_atom/cpus")
+        .map(|list| procfs::parse_cpu_list(&list).into_iter().collect())
+        .unwrap_or_default();
+
+    cpus.iter()
+        .map(|&cpu| {
+            let dir = Path::new(SYSFS_CPU).join(format!("cpu{}", cpu));
+            let tier = if !atom_cpus.is_empty() {
+                u64::from(!atom_cpus.contains(&cpu))
+            } else {
+                procfs::read_u64(dir.join("cpu_capacity")).unwrap_or(0)
+            };
+            let cluster_id = procfs::read(dir.join("topology/cluster_id"))
+                .ok()
+                .and_then(|id| id.trim().parse().ok())
+                .unwrap_or(-1);
+
+            CpuTopology {
+                cpu,
+                tier,
+                cluster_id,
+            }
+        })
+        .collect()
+}
+
+/// cpufreq policy directories and the CPUs each one governs.
+fn read_policies() -> Vec<(PathBuf, Vec<u32>)> {
+    let Ok(entries) = std::fs::read_dir(Path::new(SYSFS_CPU).join("cpufreq")) else {
+        return Vec::new();
+    };
+
+    let mut policies: Vec<(PathBuf, Vec<u32>)> = entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("policy"))
+        .filter_map(|entry| {
+            let path = entry.path();
+            let cpus = procfs::read(path.join("related_cpus")).ok()?;
+            Some((path, procfs::parse_cpu_list(&cpus.replace(' ', ","))))
+        })
+        .collect();
+    policies.sort();
+    policies
+}
+
+/// Group CPUs into named clusters, slowest core type first.
+///
+/// One tier is named "CPU" and kept whole; two are "E"/"P", three
+/// "E"/"M"/"P". On those hybrid parts a tier spanning a few cluster_ids gets
+/// one numbered cluster per id.
+fn group_clusters(topology: &[CpuTopology]) -> Vec<CpuCluster> {
+    let mut tiers: BTreeMap<u64, BTreeMap<i64, Vec<u32>>> = BTreeMap::new();
+    for cpu in topology {
+        tiers
+            .entry(cpu.tier)
+            .or_default()
+            .entry(cpu.cluster_id)
+            .or_default()
+            .push(cpu.cpu);
+    }
+
+    let tier_count = tiers.len();
+    let mut clusters = Vec::new();
+
+    for (idx, by_cluster) in tiers.into_values().enumerate() {
+        let (label, core_type) = match (tier_count, idx) {
+            (1, _) => ("CPU".to_string(), CoreType::Unknown),
+            (2, 0) | (3, 0) => ("E".to_string(), CoreType::Efficiency),
+            (2, 1) | (3, 2) => ("P".to_string(), CoreType::Performance),
+            (3, 1) => ("M".to_string(), CoreType::Mid),
+            _ => (format!("C{}", idx), CoreType::Unknown),
+        };
+
+        if tier_count == 1 || by_cluster.len() == 1 || by_cluster.len() > MAX_CLUSTERS_PER_TYPE {
+            clusters.push(CpuCluster {
+                name: if tier_count == 1 {
+                    label
+                } else {
+                    format!("{}-Cluster", label)
+                },
+                core_type,
+                cpus: by_cluster.into_values().flatten().collect(),
+                policies: Vec::new(),
+            });
+        } else {
+            for (n, cpus) in by_cluster.into_values().enumerate() {
+                clusters.push(CpuCluster {
+                    name: format!("{}{}-Cluster", label, n),
+                    core_type,
+                    cpus,
+                    policies: Vec::new(),
+                });
+            }
+        }
+    }
+
+    clusters
+}
+
+/// Per-CPU jiffies from the `cpuN` lines of /proc/stat.
+///
+/// Busy time excludes idle and iowait; guest time is already counted in
+/// user/nice, so it is left out of the total.
+fn parse_proc_stat(text: &str) -> HashMap<u32, CpuTimes> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let cpu = fields.next()?.strip_prefix("cpu")?.parse::<u32>().ok()?;
+            let values: Vec<u64> = fields.take(8).filter_map(|f| f.parse().ok()).collect();
+            if values.len() < 4 {
+                return None;
+            }
+
+            let total: u64 = values.iter().sum();
+            let idle = values[3] + values.get(4).copied().unwrap_or(0);
+            Some((
+                cpu,
+                CpuTimes {
+                    busy: total - idle,
+                    total,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Parse `stats/time_in_state`: one `<freq kHz> <time in 10ms>` pair per line.
+fn parse_time_in_state(text: &str) -> TimeInState {
+    text.lines()
+        .filter_map(|line| {
+            let (khz, time) = line.split_once(' ')?;
+            Some((khz.trim().parse().ok()?, time.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `(cpus, tier, cluster_id)` runs, e.g. `(0..4, 512, 0)`.
+    fn topology(runs: &[(std::ops::Range<u32>, u64, i64)]) -> Vec<CpuTopology> {
+        runs.iter()
+            .flat_map(|(cpus, tier, cluster_id)| {
+                cpus.clone().map(|cpu| CpuTopology {
+                    cpu,
+                    tier: *tier,
+                    cluster_id: *cluster_id,
+                })
+            })
+            .collect()
+    }
+
+    fn names(clusters: &[CpuCluster]) -> Vec<(&str, CoreType, usize)> {
+        clusters
+            .iter()
+            .map(|c| (c.name.as_str(), c.core_type, c.cpus.len()))
+            .collect()
+    }
+
+    #[test]
+    fn single_tier_is_one_cluster() {
+        // A 4-core x86 part with one cluster_id per core, then a server
+        // with one per CCX
+        let per_core = topology(&[(0..1, 0, 0), (1..2, 0, 1), (2..3, 0, 2), (3..4, 0, 3)]);
+        let per_ccx = topology(&[(0..8, 0, 0), (8..16, 0, 8)]);
+
+        for topology in [per_core, per_ccx] {
+            let clusters = group_clusters(&topology);
+            assert_eq!(
+                names(&clusters),
+                [("CPU", CoreType::Unknown, topology.len())]
+            );
+        }
+    }
+
+    #[test]
+    fn hybrid_tiers_split_by_cluster_id() {
+        // Alder Lake 8P+8E: a cluster_id per P core, one per 4-core E module
+        let mut alder_lake: Vec<CpuTopology> = (0..8)
+            .map(|cpu| CpuTopology {
+                cpu,
+                tier: 1,
+                cluster_id: cpu as i64,
+            })
+            .collect();
+        alder_lake.extend(topology(&[(8..12, 0, 8), (12..16, 0, 12)]));
+
+        assert_eq!(
+            names(&group_clusters(&alder_lake)),
+            [
+                ("E0-Cluster", CoreType::Efficiency, 4),
+                ("E1-Cluster", CoreType::Efficiency, 4),
+                ("P-Cluster", CoreType::Performance, 8),
+            ]
+        );
+
+        // Three-tier phone SoC
+        let tensor = topology(&[(0..4, 250, 0), (4..6, 620, 1), (6..8, 1024, 2)]);
+        assert_eq!(
+            names(&group_clusters(&tensor)),
+            [
+                ("E-Cluster", CoreType::Efficiency, 4),
+                ("M-Cluster", CoreType::Mid, 2),
+                ("P-Cluster", CoreType::Performance, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn proc_stat_per_cpu_jiffies() {
+        let times = parse_proc_stat(include_str!("../../tests/fixtures/procfs/stat"));
+
+        // The aggregate `cpu` line and the non-CPU lines are skipped
+        let mut cpus: Vec<u32> = times.keys().copied().collect();
+        cpus.sort();
+        assert_eq!(cpus, [0, 1, 2, 3]);
+
+        // user nice system idle iowait irq softirq steal
+        let cpu0 = times[&0];
+        assert_eq!(
+            cpu0.total,
+            103_000 + 300 + 24_500 + 1_702_000 + 1_350 + 2_900
+        );
+        assert_eq!(cpu0.busy, 103_000 + 300 + 24_500 + 2_900);
+
+        // guest time is already in user
+        let cpu2 = times[&2];
+        assert_eq!(cpu2.total, 103_000 + 300 + 24_500 + 1_702_500 + 1_350 + 50);
+        assert_eq!(cpu2.busy, 103_000 + 300 + 24_500 + 50);
+    }
+
+    #[test]
+    fn proc_stat_from_old_kernels() {
+        // 2.4 kernels stop after idle; 2.6.0 adds iowait, irq and softirq
+        let times = parse_proc_stat("cpu0 10 2 5 100\ncpu1 10 2 5 100 8 1 1\ncpu2 1 2\n");
+
+        assert_eq!(times.len(), 2);
+        assert_eq!((times[&0].busy, times[&0].total), (17, 117));
+        assert_eq!((times[&1].busy, times[&1].total), (19, 127));
+    }
+
+    #[test]
+    fn time_in_state_residency() {
+        let table = parse_time_in_state(include_str!("../../tests/fixtures/procfs/time_in_state"));
+
+        assert_eq!(table.len(), 7);
+        assert_eq!(table[&408_000], 120_000);
+        assert_eq!(table[&1_800_000], 0);
+
+        // Mostly idling at the lowest step
+        let (freq_mhz, residency) = residency_frequency(&table).unwrap();
+        assert_eq!(freq_mhz, 450);
+        assert_eq!(residency.len(), 7);
+        assert_eq!(residency[0].freq_mhz, 408);
+        assert!((residency[0].residency_pct - 120_000.0 / 132_100.0 * 100.0).abs() < 1e-9);
+        assert_eq!(residency[6].residency_pct, 0.0);
+        let total: f64 = residency.iter().map(|r| r.residency_pct).sum();
+        assert!((total - 100.0).abs() < 1e-9);
+
+        // No time elapsed
+        let idle: TimeInState = table.keys().map(|&khz| (khz, 0)).collect();
+        assert!(residency_frequency(&idle).is_none());
+        assert!(parse_time_in_state("").is_empty());
+    }
+
+    fn policies(count: u32) -> Vec<PathBuf> {
+        (0..count)
+            .map(|n| Path::new(SYSFS_CPU).join(format!("cpufreq/policy{}", n)))
+            .collect()
+    }
+
+    /// Time at 1 and 2 GHz
+    fn time_in_state(time: [u64; 2]) -> TimeInState {
+        TimeInState::from([(1_000_000, time[0]), (2_000_000, time[1])])
+    }
+
+    #[test]
+    fn cores_report_their_own_policy() {
+        let policies = policies(2);
+        let per_core = CpuStats {
+            prev_time_in_state: HashMap::from([
+                (policies[0].clone(), time_in_state([0, 0])),
+                (policies[1].clone(), time_in_state([0, 0])),
+            ]),
+            core_policies: HashMap::from([(0, policies[0].clone()), (1, policies[1].clone())]),
+            ..Default::default()
+        };
+        // Core 0 idles at 1 GHz while core 1 runs at 2 GHz
+        let samples = HashMap::from([
+            (
+                policies[0].clone(),
+                PolicySample {
+                    time_in_state: time_in_state([100, 0]),
+                    ..Default::default()
+                },
+            ),
+            (
+                policies[1].clone(),
+                PolicySample {
+                    time_in_state: time_in_state([0, 100]),
+                    ..Default::default()
+                },
+            ),
+        ]);
+        let cluster = CpuCluster {
+            name: "CPU".to_string(),
+            core_type: CoreType::Unknown,
+            cpus: vec![0, 1],
+            policies: policies.clone(),
+        };
+
+        let cluster_mhz = per_core
+            .policy_frequency(&cluster.policies, &samples)
+            .avg_mhz;
+        assert_eq!(cluster_mhz, 1500);
+        assert_eq!(
+            per_core.core_frequency(&cluster, 0, &samples, cluster_mhz),
+            1000
+        );
+        assert_eq!(
+            per_core.core_frequency(&cluster, 1, &samples, cluster_mhz),
+            2000
+        );
+        // No policy of its own
+        assert_eq!(
+            per_core.core_frequency(&cluster, 2, &samples, cluster_mhz),
+            1500
+        );
+
+        // One policy for the whole cluster
+        let shared = CpuCluster {
+            policies: vec![policies[0].clone()],
+            ..cluster
+        };
+        let shared_stats = CpuStats {
+            core_policies: HashMap::from([(0, policies[0].clone()), (1, policies[0].clone())]),
+            ..per_core
+        };
+        assert_eq!(
+            shared_stats.core_frequency(&shared, 1, &samples, 1234),
+            1234
+        );
+    }
+
+    #[test]
+    fn current_frequency_is_reported_alongside_the_average() {
+        let policies = policies(2);
+        let stats = CpuStats {
+            prev_time_in_state: HashMap::from([(policies[0].clone(), time_in_state([0, 0]))]),
+            ..Default::default()
+        };
+        let mut samples = HashMap::from([
+            (
+                policies[0].clone(),
+                PolicySample {
+                    // Mostly at 1 GHz, but boosting when sampled
+                    time_in_state: time_in_state([300, 100]),
+                    cur_khz: Some(2_000_000),
+                    max_khz: Some(2_000_000),
+                },
+            ),
+            (
+                policies[1].clone(),
+                PolicySample {
+                    // No cpufreq stats, as with intel_pstate
+                    cur_khz: Some(3_000_000),
+                    max_khz: Some(4_200_000),
+                    ..Default::default()
+                },
+            ),
+        ]);
+
+        let freq = stats.policy_frequency(&policies[..1], &samples);
+        assert_eq!(freq.avg_mhz, 1250);
+        assert_eq!(freq.cur_mhz, 2000);
+        assert_eq!(freq.max_mhz, 2000);
+        assert_eq!(freq.residency.len(), 2);
+
+        // Without time_in_state the average falls back to the current value
+        let freq = stats.policy_frequency(&policies[1..], &samples);
+        assert_eq!(
+            (freq.avg_mhz, freq.cur_mhz, freq.max_mhz),
+            (3000, 3000, 4200)
+        );
+        assert!(freq.residency.is_empty());
+
+        // Both policies: the max of the maxima, the mean of the current values
+        let freq = stats.policy_frequency(&policies, &samples);
+        assert_eq!(
+            (freq.avg_mhz, freq.cur_mhz, freq.max_mhz),
+            (1250, 2500, 4200)
+        );
+
+        // A policy that couldn't be read at all
+        samples.clear();
+        let freq = stats.policy_frequency(&policies, &samples);
+        assert_eq!((freq.avg_mhz, freq.cur_mhz, freq.max_mhz), (0, 0, 0));
+    }
+}
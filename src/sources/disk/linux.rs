@@ -0,0 +1,203 @@
+use super::{DiskSnapshot, DiskStats, DiskTiming};
+use crate::error::Error;
+use crate::sources::procfs;
+use std::collections::HashMap;
+use std::path::Path;
+
+// /proc/diskstats always counts 512-byte sectors, whatever the device block size
+const SECTOR_SIZE: u64 = 512;
+const LOOP_MAJOR: u32 = 7;
+
+impl DiskStats {
+    pub(super) fn get_disk_snapshots(&self) -> Result<HashMap<String, DiskSnapshot>, Error> {
+        let text = procfs::read("/proc/diskstats").map_err(Error::Read)?;
+
+        Ok(self.select_devices(parse_diskstats(&text), is_partition))
+    }
+
+    /// Whole disks only, unless `include_all_devices` is set.
+    fn select_devices(
+        &self,
+        rows: Vec<(String, u32, DiskSnapshot)>,
+        is_partition: impl Fn(&str) -> bool,
+    ) -> HashMap<String, DiskSnapshot> {
+        rows.into_iter()
+            .filter(|(name, major, _)| {
+                self.include_all_devices || (*major != LOOP_MAJOR && !is_partition(name))
+            })
+            .map(|(name, _, snapshot)| (name, snapshot))
+            .collect()
+    }
+}
+
+/// Partitions carry a `partition` attribute in sysfs; whole disks don't.
+fn is_partition(name: &str) -> bool {
+    Path::new("/sys/class/block")
+        .join(name)
+        .join("partition")
+        .exists()
+}
+
+/// Parse /proc/diskstats into (name, major, snapshot) rows.
+///
+/// Fields after the device name: reads, reads merged, sectors read, ms
+/// reading, writes, writes merged, sectors written, ms writing, I/Os in
+/// progress, io_ticks, time_in_queue, then optional discard/flush columns.
+fn parse_diskstats(text: &str) -> Vec<(String, u32, DiskSnapshot)> {
+    text.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 14 {
+                return None;
+            }
+
+            let major = fields[0].parse().ok()?;
+            let name = fields[2].to_string();
+            let counter = |idx: usize| fields[idx].parse::<u64>().ok();
+
+            let snapshot = DiskSnapshot {
+                read_ops: counter(3)?,
+                read_bytes: counter(5)? * SECTOR_SIZE,
+                write_ops: counter(7)?,
+                write_bytes: counter(9)? * SECTOR_SIZE,
+                timing: Some(DiskTiming {
+                    read_ms: counter(6)?,
+                    write_ms: counter(10)?,
+                    io_ticks_ms: counter(12)?,
+                    time_in_queue_ms: counter(13)?,
+                }),
+            };
+
+            Some((name, major, snapshot))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARTITIONS: [&str; 4] = ["sda1", "sda2", "nvme0n1p1", "nvme0n1p2"];
+
+    fn snapshots(stats: &DiskStats, text: &str) -> HashMap<String, DiskSnapshot> {
+        stats.select_devices(parse_diskstats(text), |name| PARTITIONS.contains(&name))
+    }
+
+    fn names(snapshots: &HashMap<String, DiskSnapshot>) -> Vec<&str> {
+        let mut names: Vec<&str> = snapshots.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn parses_every_diskstats_layout() {
+        let rows = parse_diskstats(include_str!("../../../tests/fixtures/procfs/diskstats_0"));
+
+        // 2.6-era partition lines have too few fields to use
+        let names: Vec<&str> = rows.iter().map(|(name, _, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "loop0",
+                "sda",
+                "sda1",
+                "nvme0n1",
+                "nvme0n1p1",
+                "nvme0n1p2",
+                "dm-0"
+            ]
+        );
+
+        // 14 fields (pre-4.18) and 20 fields (discard and flush columns)
+        let (_, major, sda) = &rows[1];
+        assert_eq!(*major, 8);
+        assert_eq!(sda.read_ops, 3000);
+        assert_eq!(sda.read_bytes, 240_000 * 512);
+        assert_eq!(sda.write_ops, 800);
+        assert_eq!(sda.write_bytes, 64_000 * 512);
+
+        let (_, major, nvme) = &rows[3];
+        assert_eq!(*major, 259);
+        let timing = nvme.timing.unwrap();
+        assert_eq!(timing.read_ms, 40_000);
+        assert_eq!(timing.write_ms, 60_000);
+        assert_eq!(timing.io_ticks_ms, 70_000);
+        assert_eq!(timing.time_in_queue_ms, 100_000);
+    }
+
+    #[test]
+    fn skips_partitions_and_loop_devices() {
+        let text = include_str!("../../../tests/fixtures/procfs/diskstats_0");
+
+        let disks = DiskStats::new();
+        assert_eq!(names(&snapshots(&disks, text)), ["dm-0", "nvme0n1", "sda"]);
+
+        let all = DiskStats::new().include_all_devices();
+        assert_eq!(
+            names(&snapshots(&all, text)),
+            [
+                "dm-0",
+                "loop0",
+                "nvme0n1",
+                "nvme0n1p1",
+                "nvme0n1p2",
+                "sda",
+                "sda1"
+            ]
+        );
+    }
+
+    #[test]
+    fn iostat_extended_figures() {
+        let mut stats = DiskStats::new();
+        let first = snapshots(
+            &stats,
+            include_str!("../../../tests/fixtures/procfs/diskstats_0"),
+        );
+        assert!(stats.metrics_since_prev(first, 1.0).is_empty());
+
+        let second = snapshots(
+            &stats,
+            include_str!("../../../tests/fixtures/procfs/diskstats_1"),
+        );
+        let metrics = stats.metrics_since_prev(second, 0.5);
+        let nvme = metrics.iter().find(|d| d.name == "nvme0n1").unwrap();
+
+        // 200 reads of 2 MiB and 50 writes of 1 MiB in half a second
+        assert_eq!(nvme.read_ops_per_sec, 400);
+        assert_eq!(nvme.read_bytes_per_sec, 4 << 20);
+        assert_eq!(nvme.write_ops_per_sec, 100);
+        assert_eq!(nvme.write_bytes_per_sec, 2 << 20);
+        // 250 ms busy out of 500
+        assert_eq!(nvme.util_pct, Some(50.0));
+        // 100 ms reading + 150 ms writing over 250 ops
+        assert_eq!(nvme.await_ms, Some(1.0));
+        // 500 ms of queue time over 500 ms
+        assert_eq!(nvme.queue_depth, Some(1.0));
+
+        // An idle disk waits on nothing
+        let sda = metrics.iter().find(|d| d.name == "sda").unwrap();
+        assert_eq!(sda.util_pct, Some(0.0));
+        assert_eq!(sda.await_ms, Some(0.0));
+        assert_eq!(sda.read_ops_per_sec, 0);
+    }
+
+    #[test]
+    fn util_is_capped_at_100() {
+        let mut stats = DiskStats::new();
+        let first = snapshots(
+            &stats,
+            include_str!("../../../tests/fixtures/procfs/diskstats_0"),
+        );
+        stats.metrics_since_prev(first, 1.0);
+
+        let second = snapshots(
+            &stats,
+            include_str!("../../../tests/fixtures/procfs/diskstats_1"),
+        );
+        // io_ticks advanced 250 ms; a late sample claims only 100 ms passed
+        let metrics = stats.metrics_since_prev(second, 0.1);
+        let nvme = metrics.iter().find(|d| d.name == "nvme0n1").unwrap();
+        assert_eq!(nvme.util_pct, Some(100.0));
+    }
+}
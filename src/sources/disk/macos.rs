@@ -1,4 +1,5 @@
-use crate::types::DiskMetrics;
+use super::{DiskSnapshot, DiskStats};
+use crate::error::Error;
 use core_foundation::base::TCFType;
 use core_foundation::dictionary::CFDictionaryRef;
 use core_foundation::number::CFNumberRef;
@@ -37,65 +38,23 @@ extern "C" {
 
 const K_CF_NUMBER_SINT64_TYPE: i32 = 4;
 
-#[derive(Debug, Clone, Default)]
-struct DiskSnapshot {
-    read_bytes: u64,
-    write_bytes: u64,
-    read_ops: u64,
-    write_ops: u64,
-}
-
-pub struct DiskStats {
-    prev_snapshots: HashMap<String, DiskSnapshot>,
-}
-
 impl DiskStats {
-    pub fn new() -> Self {
-        Self {
-            prev_snapshots: HashMap::new(),
-        }
-    }
-
-    pub fn get_metrics(&mut self, interval_secs: f64) -> Vec<DiskMetrics> {
-        let current = self.get_disk_snapshots();
-        let mut metrics = Vec::new();
-
-        for (name, current_snap) in &current {
-            if let Some(prev_snap) = self.prev_snapshots.get(name) {
-                let read_bytes_delta = current_snap.read_bytes.saturating_sub(prev_snap.read_bytes);
-                let write_bytes_delta =
-                    current_snap.write_bytes.saturating_sub(prev_snap.write_bytes);
-                let read_ops_delta = current_snap.read_ops.saturating_sub(prev_snap.read_ops);
-                let write_ops_delta = current_snap.write_ops.saturating_sub(prev_snap.write_ops);
-
-                metrics.push(DiskMetrics {
-                    name: name.clone(),
-                    read_bytes_per_sec: (read_bytes_delta as f64 / interval_secs) as u64,
-                    write_bytes_per_sec: (write_bytes_delta as f64 / interval_secs) as u64,
-                    read_ops_per_sec: (read_ops_delta as f64 / interval_secs) as u64,
-                    write_ops_per_sec: (write_ops_delta as f64 / interval_secs) as u64,
-                });
-            }
-        }
-
-        self.prev_snapshots = current;
-        metrics
-    }
-
-    fn get_disk_snapshots(&self) -> HashMap<String, DiskSnapshot> {
+    pub(super) fn get_disk_snapshots(&self) -> Result<HashMap<String, DiskSnapshot>, Error> {
         let mut snapshots = HashMap::new();
 
         unsafe {
-            let class_name = b"IOBlockStorageDriver\0".as_ptr() as *const i8;
-            let matching = IOServiceMatching(class_name);
+            let matching = IOServiceMatching(c"IOBlockStorageDriver".as_ptr());
             if matching.is_null() {
-                return snapshots;
+                return Err(Error::Read("IOServiceMatching failed".into()));
             }
 
             let mut iterator: IOIterator = 0;
             let result = IOServiceGetMatchingServices(0, matching, &mut iterator);
             if result != 0 {
-                return snapshots;
+                return Err(Error::Read(format!(
+                    "IOServiceGetMatchingServices failed: {}",
+                    result
+                )));
             }
 
             loop {
@@ -127,7 +86,7 @@ impl DiskStats {
             IOObjectRelease(iterator);
         }
 
-        snapshots
+        Ok(snapshots)
     }
 
     fn get_driver_stats(&self, service: IOObject) -> Option<DiskSnapshot> {
@@ -168,6 +127,7 @@ impl DiskStats {
                 write_bytes,
                 read_ops,
                 write_ops,
+                timing: None,
             })
         }
     }
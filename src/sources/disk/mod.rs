@@ -0,0 +1,125 @@
+use crate::error::Error;
+use crate::sources::{Capability, MetricSource, SampleContext};
+use crate::types::{AllMetrics, DiskMetrics};
+use std::collections::HashMap;
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+
+#[derive(Debug, Clone, Default)]
+struct DiskSnapshot {
+    read_bytes: u64,
+    write_bytes: u64,
+    read_ops: u64,
+    write_ops: u64,
+    timing: Option<DiskTiming>,
+}
+
+/// Cumulative busy-time counters in milliseconds, as in /proc/diskstats.
+#[derive(Debug, Clone, Copy, Default)]
+struct DiskTiming {
+    read_ms: u64,
+    write_ms: u64,
+    io_ticks_ms: u64,
+    time_in_queue_ms: u64,
+}
+
+#[derive(Default)]
+pub struct DiskStats {
+    prev_snapshots: HashMap<String, DiskSnapshot>,
+    include_all_devices: bool,
+}
+
+impl DiskStats {
+    pub fn new() -> Self {
+        Self {
+            prev_snapshots: HashMap::new(),
+            include_all_devices: false,
+        }
+    }
+
+    /// Also report partitions and loop devices (Linux only; macOS already
+    /// reports whole drives).
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub fn include_all_devices(mut self) -> Self {
+        self.include_all_devices = true;
+        self
+    }
+
+    pub fn get_metrics(&mut self, interval_secs: f64) -> Result<Vec<DiskMetrics>, Error> {
+        let current = self.get_disk_snapshots()?;
+        Ok(self.metrics_since_prev(current, interval_secs))
+    }
+
+    /// Rates between the previous snapshots and `current`, which become the
+    /// new baseline. Disks seen for the first time are left out.
+    fn metrics_since_prev(
+        &mut self,
+        current: HashMap<String, DiskSnapshot>,
+        interval_secs: f64,
+    ) -> Vec<DiskMetrics> {
+        let mut metrics = Vec::new();
+
+        for (name, current_snap) in &current {
+            if let Some(prev_snap) = self.prev_snapshots.get(name) {
+                let read_bytes_delta = current_snap.read_bytes.saturating_sub(prev_snap.read_bytes);
+                let write_bytes_delta = current_snap
+                    .write_bytes
+                    .saturating_sub(prev_snap.write_bytes);
+                let read_ops_delta = current_snap.read_ops.saturating_sub(prev_snap.read_ops);
+                let write_ops_delta = current_snap.write_ops.saturating_sub(prev_snap.write_ops);
+
+                let mut disk = DiskMetrics {
+                    name: name.clone(),
+                    read_bytes_per_sec: (read_bytes_delta as f64 / interval_secs) as u64,
+                    write_bytes_per_sec: (write_bytes_delta as f64 / interval_secs) as u64,
+                    read_ops_per_sec: (read_ops_delta as f64 / interval_secs) as u64,
+                    write_ops_per_sec: (write_ops_delta as f64 / interval_secs) as u64,
+                    ..Default::default()
+                };
+
+                // iostat -x: %util from io_ticks, await per completed op,
+                // aqu-sz from the weighted time_in_queue
+                if let (Some(cur), Some(prev)) = (current_snap.timing, prev_snap.timing) {
+                    let interval_ms = interval_secs * 1000.0;
+                    let io_ticks = cur.io_ticks_ms.saturating_sub(prev.io_ticks_ms) as f64;
+                    let queue_ms = cur.time_in_queue_ms.saturating_sub(prev.time_in_queue_ms);
+                    let busy_ms = cur.read_ms.saturating_sub(prev.read_ms)
+                        + cur.write_ms.saturating_sub(prev.write_ms);
+                    let ops = read_ops_delta + write_ops_delta;
+
+                    disk.util_pct = Some((io_ticks / interval_ms * 100.0).min(100.0));
+                    disk.await_ms = Some(if ops > 0 {
+                        busy_ms as f64 / ops as f64
+                    } else {
+                        0.0
+                    });
+                    disk.queue_depth = Some(queue_ms as f64 / interval_ms);
+                }
+
+                metrics.push(disk);
+            }
+        }
+
+        metrics.sort_by(|a, b| a.name.cmp(&b.name));
+        self.prev_snapshots = current;
+        metrics
+    }
+}
+
+impl MetricSource for DiskStats {
+    fn name(&self) -> &'static str {
+        "disk"
+    }
+
+    fn capabilities(&self) -> &'static [Capability] {
+        &[Capability::Disks]
+    }
+
+    fn sample(&mut self, ctx: &SampleContext, out: &mut AllMetrics) -> Result<(), Error> {
+        out.disks = self.get_metrics(ctx.interval_secs())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,249 @@
+#![cfg_attr(not(target_os = "macos"), allow(dead_code))]
+
+use super::channel::IOReportSample;
+use crate::types::{AgentBandwidth, MemoryBandwidth};
+
+pub const AMC_STATS: &str = "AMC Stats";
+pub const PMP: &str = "PMP";
+
+/// Groups carrying DRAM byte counters, preferred first. Chips that have both
+/// count the same traffic in each, so only one is used.
+const BANDWIDTH_GROUPS: &[&str] = &[PMP, AMC_STATS];
+
+const BYTES_PER_GB: f64 = 1e9;
+
+/// Display names of the agents, in display order. Channel prefixes map onto
+/// these; anything else keeps its channel name and sorts after them.
+const AGENTS: &[(&str, &[&str])] = &[
+    ("CPU", &["CPU", "ECPU", "PCPU"]),
+    ("GPU", &["GPU", "GFX"]),
+    ("ANE", &["ANE"]),
+    ("ISP", &["ISP"]),
+    ("Display", &["DISP", "DCP"]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Read,
+    Write,
+}
+
+/// What a byte counter measures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Counter {
+    /// All traffic at the memory controller (`DCS`)
+    Total(Direction),
+    /// One agent's traffic
+    Agent(String, Direction),
+}
+
+impl Counter {
+    /// Parse `[AMCC<n>] [DCS] [<agent>...] RD|WR`, e.g. `DCS RD`,
+    /// `AMCC1 PCPU DCS WR`, `GFX RD`. Memory-controller instances
+    /// (`AMCC0`, `AMCC1`, ...) are summed, so they are dropped from the name.
+    fn parse(channel: &str) -> Option<Counter> {
+        let mut tokens: Vec<&str> = channel.split_whitespace().collect();
+        let direction = match tokens.pop()? {
+            "RD" => Direction::Read,
+            "WR" => Direction::Write,
+            _ => return None,
+        };
+
+        let is_instance = |token: &str| {
+            token
+                .strip_prefix("AMCC")
+                .or_else(|| token.strip_prefix("AMC"))
+                .is_some_and(|n| n.bytes().all(|b| b.is_ascii_digit()))
+        };
+        let dcs = tokens.contains(&"DCS");
+        tokens.retain(|token| *token != "DCS" && !is_instance(token));
+
+        match tokens.first() {
+            Some(first) => Some(Counter::Agent(agent_name(first, &tokens), direction)),
+            None if dcs => Some(Counter::Total(direction)),
+            None => None,
+        }
+    }
+}
+
+/// Display name of the agent named by `tokens`, whose first is `first`.
+fn agent_name(first: &str, tokens: &[&str]) -> String {
+    let prefix: String = first
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect();
+    AGENTS
+        .iter()
+        .find(|(_, prefixes)| prefixes.contains(&prefix.as_str()))
+        .map(|(name, _)| name.to_string())
+        .unwrap_or_else(|| tokens.join(" "))
+}
+
+/// Bytes per count of a counter in `unit`.
+fn unit_scale(unit: &str) -> f64 {
+    match unit.trim() {
+        "KB" | "KiB" => 1024.0,
+        "MB" | "MiB" => 1024.0 * 1024.0,
+        _ => 1.0,
+    }
+}
+
+/// Read/write GB/s at the memory controller and per agent, from the first
+/// of `PMP` and `AMC Stats` that has byte counters. Totals come from the
+/// `DCS` counters, or are the sum of the agents where a chip has none.
+/// Returns `None` if neither group reports any.
+pub fn memory_bandwidth(samples: &[IOReportSample], interval_secs: f64) -> Option<MemoryBandwidth> {
+    if interval_secs <= 0.0 {
+        return None;
+    }
+
+    let counters = BANDWIDTH_GROUPS.iter().find_map(|group| {
+        let counters: Vec<(Counter, f64)> = samples
+            .iter()
+            .filter(|s| s.group == *group)
+            .filter_map(|s| {
+                let counter = Counter::parse(&s.channel)?;
+                let bytes = s.data.simple()?.max(0) as f64 * unit_scale(&s.unit);
+                Some((counter, bytes / interval_secs / BYTES_PER_GB))
+            })
+            .collect();
+        (!counters.is_empty()).then_some(counters)
+    })?;
+
+    let mut bandwidth = MemoryBandwidth::default();
+    let mut has_total = false;
+    for (counter, gbps) in counters {
+        match counter {
+            Counter::Total(direction) => {
+                has_total = true;
+                match direction {
+                    Direction::Read => bandwidth.read_gbps += gbps,
+                    Direction::Write => bandwidth.write_gbps += gbps,
+                }
+            }
+            Counter::Agent(name, direction) => {
+                let idx = match bandwidth.agents.iter().position(|a| a.name == name) {
+                    Some(idx) => idx,
+                    None => {
+                        bandwidth.agents.push(AgentBandwidth {
+                            name,
+                            ..Default::default()
+                        });
+                        bandwidth.agents.len() - 1
+                    }
+                };
+                let agent = &mut bandwidth.agents[idx];
+                match direction {
+                    Direction::Read => agent.read_gbps += gbps,
+                    Direction::Write => agent.write_gbps += gbps,
+                }
+            }
+        }
+    }
+
+    if !has_total {
+        bandwidth.read_gbps = bandwidth.agents.iter().map(|a| a.read_gbps).sum();
+        bandwidth.write_gbps = bandwidth.agents.iter().map(|a| a.write_gbps).sum();
+    }
+    bandwidth.agents.sort_by(|a, b| {
+        let rank = |name: &str| {
+            AGENTS
+                .iter()
+                .position(|(known, _)| *known == name)
+                .unwrap_or(AGENTS.len())
+        };
+        rank(&a.name)
+            .cmp(&rank(&b.name))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    Some(bandwidth)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::ioreport::channel::IOReportFixture;
+    use crate::test_util::assert_close;
+
+    fn agents(bandwidth: &MemoryBandwidth) -> Vec<&str> {
+        bandwidth.agents.iter().map(|a| a.name.as_str()).collect()
+    }
+
+    #[test]
+    fn parses_counter_names() {
+        use Direction::*;
+
+        assert_eq!(Counter::parse("DCS RD"), Some(Counter::Total(Read)));
+        assert_eq!(Counter::parse("AMCC1 DCS WR"), Some(Counter::Total(Write)));
+        assert_eq!(
+            Counter::parse("AMCC0 PCPU DCS RD"),
+            Some(Counter::Agent("CPU".into(), Read))
+        );
+        assert_eq!(
+            Counter::parse("GFX WR"),
+            Some(Counter::Agent("GPU".into(), Write))
+        );
+        assert_eq!(
+            Counter::parse("DISP0 RD"),
+            Some(Counter::Agent("Display".into(), Read))
+        );
+        assert_eq!(
+            Counter::parse("AVE RD"),
+            Some(Counter::Agent("AVE".into(), Read))
+        );
+        assert_eq!(Counter::parse("AMCC0 RD"), None);
+        assert_eq!(Counter::parse("DCS Latency"), None);
+        assert_eq!(Counter::parse(""), None);
+    }
+
+    #[test]
+    fn sums_controllers_and_agents() {
+        let fixture = IOReportFixture::from_json(include_str!(
+            "../../../tests/fixtures/ioreport/amc_stats.json"
+        ))
+        .unwrap();
+        let bandwidth = memory_bandwidth(&fixture.samples, fixture.interval_secs()).unwrap();
+
+        // Two controllers, 1.5 + 2.5 GB read and 0.5 + 0.5 GB written in 0.5 s
+        assert_close(bandwidth.read_gbps, 8.0);
+        assert_close(bandwidth.write_gbps, 2.0);
+        assert_eq!(
+            agents(&bandwidth),
+            ["CPU", "GPU", "ANE", "ISP", "Display", "AVE"]
+        );
+        // ECPU and PCPU, on both controllers
+        assert_close(bandwidth.agents[0].read_gbps, 3.0);
+        assert_close(bandwidth.agents[0].write_gbps, 1.0);
+        // Counted in KiB
+        assert_close(
+            bandwidth.agents[4].read_gbps,
+            1_000_000.0 * 1024.0 / 0.5 / 1e9,
+        );
+    }
+
+    #[test]
+    fn prefers_pmp_and_sums_agents_without_totals() {
+        let fixture = IOReportFixture::from_json(include_str!(
+            "../../../tests/fixtures/ioreport/pmp_bandwidth.json"
+        ))
+        .unwrap();
+        let bandwidth = memory_bandwidth(&fixture.samples, fixture.interval_secs()).unwrap();
+
+        // The AMC Stats counters in the same fixture are ignored
+        assert_eq!(agents(&bandwidth), ["CPU", "GPU"]);
+        assert_close(bandwidth.read_gbps, 3.0);
+        assert_close(bandwidth.write_gbps, 1.0);
+    }
+
+    #[test]
+    fn no_counters_yield_none() {
+        let fixture = IOReportFixture::from_json(include_str!(
+            "../../../tests/fixtures/ioreport/gpu_performance_states.json"
+        ))
+        .unwrap();
+
+        assert!(memory_bandwidth(&fixture.samples, fixture.interval_secs()).is_none());
+        assert!(memory_bandwidth(&[], 1.0).is_none());
+    }
+}
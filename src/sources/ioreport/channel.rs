@@ -0,0 +1,359 @@
+#![cfg_attr(not(target_os = "macos"), allow(dead_code))]
+
+use super::bandwidth::{AMC_STATS, PMP};
+use crate::error::Error;
+use crate::types::{IOReportChannel, StateResidency};
+use serde::{Deserialize, Serialize};
+
+/// States in which a CPU cluster or the GPU does no work. Every other state
+/// of a performance-state channel is an active P-state.
+const INACTIVE_STATES: &[&str] = &["OFF", "IDLE", "DOWN"];
+
+/// One channel's delta between two IOReport samples.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IOReportSample {
+    pub group: String,
+    #[serde(default)]
+    pub subgroup: String,
+    pub channel: String,
+    #[serde(default)]
+    pub unit: String,
+    #[serde(flatten)]
+    pub data: IOReportChannel,
+}
+
+impl IOReportChannel {
+    /// The counter of a simple channel.
+    pub fn simple(&self) -> Option<i64> {
+        match self {
+            IOReportChannel::Simple { value } => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The residency table of a state channel; empty for other formats.
+    pub fn states(&self) -> &[StateResidency] {
+        match self {
+            IOReportChannel::State { states } => states,
+            _ => &[],
+        }
+    }
+}
+
+impl StateResidency {
+    /// False for OFF/IDLE/DOWN, true for P-states.
+    pub fn is_active(&self) -> bool {
+        !INACTIVE_STATES.contains(&self.name.as_str())
+    }
+}
+
+/// Channel groups subscribed for CPU/GPU residency, energy and DRAM traffic.
+/// Groups a chip does not have are skipped.
+pub const CHANNEL_GROUPS: &[(&str, Option<&str>)] = &[
+    ("Energy Model", None),
+    ("CPU Stats", Some("CPU Complex Performance States")),
+    ("CPU Stats", Some("CPU Core Performance States")),
+    ("GPU Stats", Some("GPU Performance States")),
+    (AMC_STATS, None),
+    (PMP, None),
+];
+
+/// `groups` plus the (group, subgroup) of each spec they don't already
+/// cover. IOReport delivers a channel once per subscription that covers
+/// it, so subscribing one twice would double count it.
+pub fn subscription_groups<'a>(
+    groups: &[(&'a str, Option<&'a str>)],
+    specs: &'a [ChannelSpec],
+) -> Vec<(&'a str, Option<&'a str>)> {
+    let mut groups = groups.to_vec();
+    for spec in specs {
+        let group = spec.group.as_str();
+        let subgroup = Some(spec.subgroup.as_str()).filter(|s| !s.is_empty());
+        if !groups.contains(&(group, None)) && !groups.contains(&(group, subgroup)) {
+            groups.push((group, subgroup));
+        }
+    }
+    groups
+}
+
+/// A channel picked by name, as given to `--ioreport-channel`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelSpec {
+    pub group: String,
+    /// May be empty for channels outside any subgroup
+    pub subgroup: String,
+    pub channel: String,
+}
+
+impl ChannelSpec {
+    /// Parse "Group/Subgroup/Channel". Only the first two slashes separate,
+    /// so channel names may contain slashes.
+    pub fn parse(spec: &str) -> Result<ChannelSpec, Error> {
+        let mut parts = spec.splitn(3, '/');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(group), Some(subgroup), Some(channel))
+                if !group.is_empty() && !channel.is_empty() =>
+            {
+                Ok(ChannelSpec {
+                    group: group.to_string(),
+                    subgroup: subgroup.to_string(),
+                    channel: channel.to_string(),
+                })
+            }
+            _ => Err(Error::InvalidChannel(spec.to_string())),
+        }
+    }
+
+    pub fn matches(&self, sample: &IOReportSample) -> bool {
+        sample.group == self.group
+            && sample.subgroup == self.subgroup
+            && sample.channel == self.channel
+    }
+}
+
+impl std::fmt::Display for ChannelSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}/{}", self.group, self.subgroup, self.channel)
+    }
+}
+
+/// A recorded set of channel deltas and the interval they cover, as written
+/// by `bustop ioreport dump`, for testing the decoders without IOReport.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IOReportFixture {
+    pub interval_ms: u64,
+    pub samples: Vec<IOReportSample>,
+}
+
+#[cfg(test)]
+impl IOReportFixture {
+    pub fn from_json(text: &str) -> Result<Self, Error> {
+        serde_json::from_str(text)
+            .map_err(|e| Error::Read(format!("invalid IOReport fixture: {}", e)))
+    }
+
+    /// XML or binary property list, e.g. converted with `plutil`.
+    pub fn from_plist(bytes: &[u8]) -> Result<Self, Error> {
+        plist::from_bytes(bytes)
+            .map_err(|e| Error::Read(format!("invalid IOReport fixture: {}", e)))
+    }
+
+    pub fn interval_secs(&self) -> f64 {
+        self.interval_ms as f64 / 1000.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::ioreport::bandwidth::memory_bandwidth;
+    use crate::sources::ioreport::energy::power_tree;
+    use crate::types::HistogramBucket;
+
+    #[test]
+    fn json_fixture_infers_channel_format() {
+        let fixture = IOReportFixture::from_json(include_str!(
+            "../../../tests/fixtures/ioreport/channel_formats.json"
+        ))
+        .unwrap();
+
+        assert_eq!(fixture.interval_secs(), 0.5);
+        let data: Vec<&IOReportChannel> = fixture.samples.iter().map(|s| &s.data).collect();
+        assert_eq!(data[0].simple(), Some(1834));
+        assert_eq!(
+            data[1].states(),
+            [
+                StateResidency {
+                    name: "IDLE".into(),
+                    residency: 300
+                },
+                StateResidency {
+                    name: "V0P1".into(),
+                    residency: 200
+                },
+            ]
+        );
+        assert_eq!(data[1].simple(), None);
+        assert_eq!(
+            *data[2],
+            IOReportChannel::Histogram {
+                buckets: vec![
+                    HistogramBucket {
+                        min: 0,
+                        max: 1024,
+                        hits: 7
+                    },
+                    HistogramBucket {
+                        min: 1024,
+                        max: 4096,
+                        hits: 2
+                    },
+                ]
+            }
+        );
+        assert!(data[2].states().is_empty());
+    }
+
+    #[test]
+    fn plist_and_json_fixtures_agree() {
+        let json = IOReportFixture::from_json(include_str!(
+            "../../../tests/fixtures/ioreport/channel_formats.json"
+        ))
+        .unwrap();
+        let plist = IOReportFixture::from_plist(include_bytes!(
+            "../../../tests/fixtures/ioreport/channel_formats.plist"
+        ))
+        .unwrap();
+
+        assert_eq!(plist.interval_ms, json.interval_ms);
+        assert_eq!(plist.samples, json.samples);
+    }
+
+    #[test]
+    fn json_round_trips() {
+        let fixture = IOReportFixture::from_json(include_str!(
+            "../../../tests/fixtures/ioreport/channel_formats.json"
+        ))
+        .unwrap();
+        let text = serde_json::to_string(&fixture).unwrap();
+
+        assert_eq!(
+            IOReportFixture::from_json(&text).unwrap().samples,
+            fixture.samples
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_fixture() {
+        assert!(IOReportFixture::from_json(r#"{"samples": []}"#).is_err());
+        assert!(IOReportFixture::from_plist(b"not a plist").is_err());
+    }
+
+    #[test]
+    fn parses_channel_specs() {
+        let spec = ChannelSpec::parse("GPU Stats/GPU Performance States/GPUPH").unwrap();
+        assert_eq!(spec.group, "GPU Stats");
+        assert_eq!(spec.subgroup, "GPU Performance States");
+        assert_eq!(spec.channel, "GPUPH");
+        assert_eq!(spec.to_string(), "GPU Stats/GPU Performance States/GPUPH");
+
+        // No subgroup, and a slash inside the channel name
+        let spec = ChannelSpec::parse("Energy Model//DRAM/0").unwrap();
+        assert_eq!(spec.subgroup, "");
+        assert_eq!(spec.channel, "DRAM/0");
+
+        for bad in [
+            "",
+            "Energy Model",
+            "Energy Model/DRAM",
+            "/Sub/Channel",
+            "Group/Sub/",
+        ] {
+            assert!(
+                matches!(ChannelSpec::parse(bad), Err(Error::InvalidChannel(_))),
+                "{:?}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn channel_spec_matches_exact_names() {
+        let fixture = IOReportFixture::from_json(include_str!(
+            "../../../tests/fixtures/ioreport/channel_formats.json"
+        ))
+        .unwrap();
+        let matched = |spec: &str| {
+            let spec = ChannelSpec::parse(spec).unwrap();
+            fixture
+                .samples
+                .iter()
+                .filter(|s| spec.matches(s))
+                .map(|s| s.channel.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            matched("CPU Stats/CPU Complex Performance States/ECPU"),
+            ["ECPU"]
+        );
+        assert_eq!(matched("Energy Model//CPU Energy"), ["CPU Energy"]);
+        assert!(matched("CPU Stats/CPU Complex Performance States/ECP").is_empty());
+        assert!(matched("CPU Stats//ECPU").is_empty());
+    }
+
+    #[test]
+    fn inactive_states() {
+        let state = |name: &str| StateResidency {
+            name: name.into(),
+            residency: 1,
+        };
+        assert!(!state("IDLE").is_active());
+        assert!(!state("OFF").is_active());
+        assert!(!state("DOWN").is_active());
+        assert!(state("P1").is_active());
+        assert!(state("V0P5").is_active());
+    }
+
+    /// The samples a subscription to `groups` delivers: one copy of each
+    /// channel per group entry that covers it.
+    fn subscribed(
+        samples: &[IOReportSample],
+        groups: &[(&str, Option<&str>)],
+    ) -> Vec<IOReportSample> {
+        groups
+            .iter()
+            .flat_map(|&(group, subgroup)| {
+                samples.iter().filter(move |s| {
+                    s.group == group && subgroup.map_or(true, |sub| s.subgroup == sub)
+                })
+            })
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn passthrough_channels_are_not_subscribed_twice() {
+        let specs: Vec<ChannelSpec> = [
+            "PMP/DCS BW/GFX RD",
+            "Energy Model//GPU Energy",
+            "GPU Stats/GPU Performance States/GPUPH",
+            "SoC Stats/Cluster Power States/PCPU",
+        ]
+        .iter()
+        .map(|spec| ChannelSpec::parse(spec).unwrap())
+        .collect();
+
+        let groups = subscription_groups(CHANNEL_GROUPS, &specs);
+        assert_eq!(groups.len(), CHANNEL_GROUPS.len() + 1);
+        assert_eq!(
+            groups.last(),
+            Some(&("SoC Stats", Some("Cluster Power States")))
+        );
+
+        let mut samples = IOReportFixture::from_json(include_str!(
+            "../../../tests/fixtures/ioreport/pmp_bandwidth.json"
+        ))
+        .unwrap()
+        .samples;
+        samples.extend(
+            IOReportFixture::from_json(include_str!(
+                "../../../tests/fixtures/ioreport/energy_model_tree.json"
+            ))
+            .unwrap()
+            .samples,
+        );
+        let plain = subscribed(&samples, CHANNEL_GROUPS);
+        let with_passthrough = subscribed(&samples, &groups);
+
+        let read_gbps = |samples: &[IOReportSample]| {
+            memory_bandwidth(samples, 0.5).map(|bandwidth| bandwidth.read_gbps)
+        };
+        assert!(read_gbps(&plain).is_some());
+        assert_eq!(read_gbps(&with_passthrough), read_gbps(&plain));
+
+        let tree =
+            |samples: &[IOReportSample]| serde_json::to_value(power_tree(samples, 0.5)).unwrap();
+        assert_eq!(tree(&with_passthrough), tree(&plain));
+    }
+}
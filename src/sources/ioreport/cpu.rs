@@ -0,0 +1,470 @@
+#![cfg_attr(not(target_os = "macos"), allow(dead_code))]
+
+use super::channel::IOReportSample;
+use super::dvfs::{frequency_residency, DvfsTables};
+use crate::types::{CoreType, CpuClusterMetrics, CpuCoreMetrics};
+
+const CPU_STATS: &str = "CPU Stats";
+const CPU_COMPLEX_STATES: &str = "CPU Complex Performance States";
+const CPU_CORE_STATES: &str = "CPU Core Performance States";
+
+/// A CPU cluster as named by its residency channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ClusterId {
+    pub die: u32,
+    pub core_type: CoreType,
+    /// Position among the clusters of this type on the die
+    pub index: u32,
+}
+
+impl ClusterId {
+    /// Parse `[DIE_<n>_]ECPU[<i>]` / `[DIE_<n>_]PCPU[<i>]`, e.g. `ECPU`,
+    /// `PCPU1`, `DIE_1_PCPU`. Other channels (and per-core names such as
+    /// `PCPU000`, whose index has three digits) are not clusters.
+    pub fn parse(channel: &str) -> Option<ClusterId> {
+        let (die, core_type, digits) = split_channel(channel)?;
+        let index = match digits {
+            "" => 0,
+            digits if digits.len() <= 2 => digits.parse().ok()?,
+            _ => return None,
+        };
+
+        Some(ClusterId {
+            die,
+            core_type,
+            index,
+        })
+    }
+}
+
+/// A core as named by its residency channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CoreId {
+    pub cluster: ClusterId,
+    /// Position within the cluster
+    pub core: u32,
+}
+
+impl CoreId {
+    /// Parse `[DIE_<n>_]ECPU<c><k>0` / `[DIE_<n>_]PCPU<c><k>0`: core `k` of
+    /// cluster `c`, e.g. `PCPU110` is the second core of the second P
+    /// cluster.
+    pub fn parse(channel: &str) -> Option<CoreId> {
+        let (die, core_type, digits) = split_channel(channel)?;
+        let digits = digits.as_bytes();
+        if digits.len() != 3 {
+            return None;
+        }
+
+        Some(CoreId {
+            cluster: ClusterId {
+                die,
+                core_type,
+                index: u32::from(digits[0] - b'0'),
+            },
+            core: u32::from(digits[1] - b'0'),
+        })
+    }
+}
+
+/// Die, core type and the trailing digits of a CPU residency channel name.
+fn split_channel(channel: &str) -> Option<(u32, CoreType, &str)> {
+    let (die, rest) = match channel.strip_prefix("DIE_") {
+        Some(rest) => {
+            let (die, rest) = rest.split_once('_')?;
+            (die.parse().ok()?, rest)
+        }
+        None => (0, channel),
+    };
+
+    let (core_type, digits) = if let Some(digits) = rest.strip_prefix("ECPU") {
+        (CoreType::Efficiency, digits)
+    } else if let Some(digits) = rest.strip_prefix("PCPU") {
+        (CoreType::Performance, digits)
+    } else {
+        return None;
+    };
+
+    digits
+        .bytes()
+        .all(|b| b.is_ascii_digit())
+        .then_some((die, core_type, digits))
+}
+
+/// Residency of one cluster or core channel over the sample interval.
+struct Residency<K> {
+    key: K,
+    /// Time in each active P-state, in channel order
+    active: Vec<i64>,
+    /// Time in all states
+    total: i64,
+}
+
+impl<K> Residency<K> {
+    fn active_pct(&self) -> f64 {
+        let active: i64 = self.active.iter().sum();
+        (active as f64 / self.total as f64 * 100.0).min(100.0)
+    }
+}
+
+/// Residency per channel of `subgroup`, keyed by `parse`, sorted by key.
+fn residencies<K: Copy + Ord>(
+    samples: &[IOReportSample],
+    subgroup: &str,
+    parse: impl Fn(&str) -> Option<K>,
+) -> Vec<Residency<K>> {
+    let mut totals: Vec<Residency<K>> = Vec::new();
+
+    for sample in samples
+        .iter()
+        .filter(|s| s.group == CPU_STATS && s.subgroup == subgroup)
+    {
+        let Some(key) = parse(&sample.channel) else {
+            continue;
+        };
+        let idx = match totals.iter().position(|r| r.key == key) {
+            Some(idx) => idx,
+            None => {
+                totals.push(Residency {
+                    key,
+                    active: Vec::new(),
+                    total: 0,
+                });
+                totals.len() - 1
+            }
+        };
+
+        let entry = &mut totals[idx];
+        let active = sample.data.states().iter().filter(|s| s.is_active());
+        for (n, state) in active.enumerate() {
+            match entry.active.get_mut(n) {
+                Some(time) => *time += state.residency.max(0),
+                None => entry.active.push(state.residency.max(0)),
+            }
+        }
+        entry.total += sample
+            .data
+            .states()
+            .iter()
+            .map(|s| s.residency.max(0))
+            .sum::<i64>();
+    }
+
+    totals.sort_by_key(|r| r.key);
+    totals
+}
+
+/// One entry per cluster channel, ordered by die, core type and index.
+/// Active % is the time in P-states over all time; frequencies come from the
+/// core type's DVFS table in `tables`.
+pub fn cluster_metrics(samples: &[IOReportSample], tables: &DvfsTables) -> Vec<CpuClusterMetrics> {
+    let mut clusters = residencies(samples, CPU_COMPLEX_STATES, ClusterId::parse);
+    clusters.retain(|r| r.total > 0);
+
+    let ids: Vec<ClusterId> = clusters.iter().map(|r| r.key).collect();
+    clusters
+        .iter()
+        .map(|r| {
+            let id = r.key;
+            let freqs_mhz = tables.cpu(id.core_type);
+            let (freq_mhz, freq_residency) = frequency_residency(&r.active, r.total, freqs_mhz);
+            let active_pct = r.active_pct();
+            CpuClusterMetrics {
+                name: cluster_name(id, &ids),
+                die: id.die,
+                core_type: id.core_type,
+                freq_mhz,
+                freq_cur_mhz: 0, // IOReport only reports residency
+                freq_max_mhz: freqs_mhz.iter().copied().max().unwrap_or(0),
+                active_pct,
+                idle_pct: 100.0 - active_pct,
+                power_watts: 0.0, // Part of system.cpu_power_watts
+                freq_residency,
+            }
+        })
+        .collect()
+}
+
+/// One entry per core channel that reports time. Cores are numbered in
+/// channel order (die, E before P, cluster, core), which is how macOS
+/// numbers its CPUs.
+pub fn core_metrics(samples: &[IOReportSample], tables: &DvfsTables) -> Vec<CpuCoreMetrics> {
+    let cores = residencies(samples, CPU_CORE_STATES, CoreId::parse);
+
+    // Name cores after the same clusters as `cluster_metrics`, plus any
+    // cluster that only has per-core channels
+    let mut ids: Vec<ClusterId> = residencies(samples, CPU_COMPLEX_STATES, ClusterId::parse)
+        .into_iter()
+        .filter(|r| r.total > 0)
+        .map(|r| r.key)
+        .chain(cores.iter().map(|r| r.key.cluster))
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+
+    cores
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| r.total > 0)
+        .map(|(n, r)| {
+            let freqs_mhz = tables.cpu(r.key.cluster.core_type);
+            CpuCoreMetrics {
+                id: n as u32,
+                cluster: cluster_name(r.key.cluster, &ids),
+                active_pct: r.active_pct(),
+                freq_mhz: frequency_residency(&r.active, r.total, freqs_mhz).0,
+            }
+        })
+        .collect()
+}
+
+/// "E-Cluster"/"P-Cluster", numbered ("P0-Cluster", "P1-Cluster") where a
+/// die has several of a type, and prefixed with the die ("D1-P-Cluster") on
+/// multi-die chips.
+fn cluster_name(id: ClusterId, all: &[ClusterId]) -> String {
+    let label = match id.core_type {
+        CoreType::Efficiency => "E",
+        _ => "P",
+    };
+    let siblings = all
+        .iter()
+        .filter(|c| c.die == id.die && c.core_type == id.core_type)
+        .count();
+    let multi_die = all.iter().any(|c| c.die != id.die);
+
+    let mut name = String::new();
+    if multi_die {
+        name.push_str(&format!("D{}-", id.die));
+    }
+    name.push_str(label);
+    if siblings > 1 {
+        name.push_str(&id.index.to_string());
+    }
+    name.push_str("-Cluster");
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::ioreport::channel::IOReportFixture;
+    use crate::types::CpuRollup;
+
+    fn fixture(json: &str) -> Vec<IOReportSample> {
+        IOReportFixture::from_json(json).unwrap().samples
+    }
+
+    fn names(clusters: &[CpuClusterMetrics]) -> Vec<&str> {
+        clusters.iter().map(|c| c.name.as_str()).collect()
+    }
+
+    #[test]
+    fn parses_cluster_channel_names() {
+        let id = |die, core_type, index| {
+            Some(ClusterId {
+                die,
+                core_type,
+                index,
+            })
+        };
+        assert_eq!(ClusterId::parse("ECPU"), id(0, CoreType::Efficiency, 0));
+        assert_eq!(ClusterId::parse("ECPU0"), id(0, CoreType::Efficiency, 0));
+        assert_eq!(ClusterId::parse("PCPU1"), id(0, CoreType::Performance, 1));
+        assert_eq!(
+            ClusterId::parse("DIE_1_PCPU"),
+            id(1, CoreType::Performance, 0)
+        );
+        assert_eq!(
+            ClusterId::parse("DIE_1_PCPU1"),
+            id(1, CoreType::Performance, 1)
+        );
+        assert_eq!(ClusterId::parse("PCPU000"), None);
+        assert_eq!(ClusterId::parse("DIE_X_ECPU"), None);
+        assert_eq!(ClusterId::parse("GPUPH"), None);
+        assert_eq!(ClusterId::parse("ECPUX"), None);
+    }
+
+    #[test]
+    fn active_pct_comes_from_state_residency() {
+        let clusters = cluster_metrics(
+            &fixture(include_str!(
+                "../../../tests/fixtures/ioreport/cpu_performance_states.json"
+            )),
+            &DvfsTables::default(),
+        );
+
+        assert_eq!(names(&clusters), ["E-Cluster", "P0-Cluster", "P1-Cluster"]);
+        // ECPU: 250 of 1000 ticks outside IDLE
+        assert!((clusters[0].active_pct - 25.0).abs() < 1e-9);
+        assert!((clusters[0].idle_pct - 75.0).abs() < 1e-9);
+        // PCPU: 400 of 1000, PCPU1: 100 of 1000 outside IDLE/OFF
+        assert!((clusters[1].active_pct - 40.0).abs() < 1e-9);
+        assert!((clusters[2].active_pct - 10.0).abs() < 1e-9);
+        assert!(clusters.iter().all(|c| c.die == 0));
+    }
+
+    #[test]
+    fn discovers_clusters_on_every_die() {
+        let clusters = cluster_metrics(
+            &fixture(include_str!(
+                "../../../tests/fixtures/ioreport/cpu_clusters_two_dies.json"
+            )),
+            &DvfsTables::default(),
+        );
+
+        assert_eq!(
+            names(&clusters),
+            [
+                "D0-E-Cluster",
+                "D0-P0-Cluster",
+                "D0-P1-Cluster",
+                "D1-E-Cluster",
+                "D1-P0-Cluster",
+                "D1-P1-Cluster",
+            ]
+        );
+        let dies: Vec<u32> = clusters.iter().map(|c| c.die).collect();
+        assert_eq!(dies, [0, 0, 0, 1, 1, 1]);
+        assert_eq!(clusters[3].core_type, CoreType::Efficiency);
+        assert_eq!(clusters[5].core_type, CoreType::Performance);
+        // DIE_1_PCPU1 is fully busy, nothing is merged into it
+        assert!((clusters[5].active_pct - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rolls_up_by_die_and_core_type() {
+        let clusters = cluster_metrics(
+            &fixture(include_str!(
+                "../../../tests/fixtures/ioreport/cpu_clusters_two_dies.json"
+            )),
+            &DvfsTables::default(),
+        );
+        let rollups = CpuRollup::from_clusters(&clusters);
+
+        let names: Vec<&str> = rollups.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["die0", "die1", "efficiency", "performance"]);
+        let clusters: Vec<u32> = rollups.iter().map(|r| r.clusters).collect();
+        assert_eq!(clusters, [3, 3, 2, 4]);
+        // (10 + 50 + 0) / 3 and (20 + 30 + 100) / 3
+        assert!((rollups[0].active_pct - 20.0).abs() < 1e-9);
+        assert!((rollups[1].active_pct - 50.0).abs() < 1e-9);
+        assert!((rollups[2].active_pct - 15.0).abs() < 1e-9);
+        assert!((rollups[3].active_pct - 45.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn single_die_has_no_die_rollups() {
+        let clusters = cluster_metrics(
+            &fixture(include_str!(
+                "../../../tests/fixtures/ioreport/cpu_performance_states.json"
+            )),
+            &DvfsTables::default(),
+        );
+        let rollups = CpuRollup::from_clusters(&clusters);
+
+        let names: Vec<&str> = rollups.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["efficiency", "performance"]);
+    }
+
+    #[test]
+    fn parses_core_channel_names() {
+        let core = |core_type, index, core| {
+            Some(CoreId {
+                cluster: ClusterId {
+                    die: 0,
+                    core_type,
+                    index,
+                },
+                core,
+            })
+        };
+        assert_eq!(CoreId::parse("ECPU000"), core(CoreType::Efficiency, 0, 0));
+        assert_eq!(CoreId::parse("PCPU110"), core(CoreType::Performance, 1, 1));
+        assert_eq!(
+            CoreId::parse("DIE_1_PCPU030").map(|c| c.cluster.die),
+            Some(1)
+        );
+        assert_eq!(CoreId::parse("PCPU1"), None);
+        assert_eq!(CoreId::parse("PCPU"), None);
+    }
+
+    #[test]
+    fn per_core_breakdown_shows_a_pinned_core() {
+        let cores = core_metrics(
+            &fixture(include_str!(
+                "../../../tests/fixtures/ioreport/cpu_performance_states.json"
+            )),
+            &DvfsTables::default(),
+        );
+
+        // PCPU010 reports no time, but still takes its CPU number
+        let ids: Vec<u32> = cores.iter().map(|c| c.id).collect();
+        assert_eq!(ids, [0, 1, 2, 4]);
+        let clusters: Vec<&str> = cores.iter().map(|c| c.cluster.as_str()).collect();
+        assert_eq!(
+            clusters,
+            ["E-Cluster", "E-Cluster", "P0-Cluster", "P1-Cluster"]
+        );
+        let active: Vec<f64> = cores.iter().map(|c| c.active_pct).collect();
+        assert_eq!(active, [10.0, 40.0, 80.0, 100.0]);
+    }
+
+    #[test]
+    fn frequency_comes_from_the_core_types_dvfs_table() {
+        let tables = DvfsTables {
+            ecpu_mhz: vec![600, 972, 1332, 1704, 2064],
+            pcpu_mhz: vec![600, 1500, 2400, 3144, 3204],
+            gpu_mhz: Vec::new(),
+        };
+        let samples = fixture(include_str!(
+            "../../../tests/fixtures/ioreport/cpu_performance_states.json"
+        ));
+        let clusters = cluster_metrics(&samples, &tables);
+
+        // ECPU's two P-states are the top two E entries: (1704 * 150 + 2064 * 100) / 250
+        assert_eq!(clusters[0].freq_mhz, 1848);
+        assert_eq!(clusters[0].freq_max_mhz, 2064);
+        let residency: Vec<(u32, f64)> = clusters[0]
+            .freq_residency
+            .iter()
+            .map(|r| (r.freq_mhz, r.residency_pct))
+            .collect();
+        assert_eq!(residency, [(1704, 15.0), (2064, 10.0)]);
+        // (3144 * 300 + 3204 * 100) / 400
+        assert_eq!(clusters[1].freq_mhz, 3159);
+        assert_eq!(clusters[2].freq_mhz, 3204);
+        assert_eq!(clusters[2].freq_max_mhz, 3204);
+
+        let cores = core_metrics(&samples, &tables);
+        let freqs: Vec<u32> = cores.iter().map(|c| c.freq_mhz).collect();
+        assert_eq!(freqs, [2064, 2064, 3204, 3204]);
+    }
+
+    #[test]
+    fn missing_dvfs_table_leaves_frequency_unknown() {
+        let clusters = cluster_metrics(
+            &fixture(include_str!(
+                "../../../tests/fixtures/ioreport/cpu_performance_states.json"
+            )),
+            &DvfsTables::default(),
+        );
+
+        assert!(clusters
+            .iter()
+            .all(|c| c.freq_mhz == 0 && c.freq_max_mhz == 0));
+        assert!(clusters.iter().all(|c| c.freq_residency.is_empty()));
+        assert!((clusters[0].active_pct - 25.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn per_core_channels_do_not_count_towards_clusters() {
+        let samples: Vec<IOReportSample> = fixture(include_str!(
+            "../../../tests/fixtures/ioreport/cpu_performance_states.json"
+        ))
+        .into_iter()
+        .filter(|s| s.subgroup != CPU_COMPLEX_STATES)
+        .collect();
+
+        assert!(cluster_metrics(&samples, &DvfsTables::default()).is_empty());
+    }
+}
@@ -0,0 +1,213 @@
+#![cfg_attr(not(target_os = "macos"), allow(dead_code))]
+
+use crate::types::{CoreType, FreqResidency};
+
+/// Raw frequencies above this are in Hz; older chips store Hz, M4-class
+/// tables store kHz. No DVFS state runs below 10 MHz or above 10 GHz, so
+/// the ranges cannot be confused.
+const HZ_THRESHOLD: u32 = 10_000_000;
+
+/// One operating point of a `voltage-states` table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VoltageState {
+    pub freq_mhz: u32,
+    pub voltage_mv: u32,
+}
+
+/// The chip's DVFS frequency tables in MHz, lowest first. Empty tables leave
+/// the matching frequencies at 0.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DvfsTables {
+    pub ecpu_mhz: Vec<u32>,
+    pub pcpu_mhz: Vec<u32>,
+    pub gpu_mhz: Vec<u32>,
+}
+
+impl DvfsTables {
+    /// The table for clusters of `core_type`.
+    pub fn cpu(&self, core_type: CoreType) -> &[u32] {
+        match core_type {
+            CoreType::Efficiency => &self.ecpu_mhz,
+            CoreType::Performance => &self.pcpu_mhz,
+            _ => &[],
+        }
+    }
+}
+
+/// Decode a `pmgr` `voltage-states` property: packed little-endian u32
+/// (frequency, voltage) pairs, lowest state first. A trailing partial pair
+/// is ignored.
+pub fn decode_voltage_states(blob: &[u8]) -> Vec<VoltageState> {
+    blob.chunks_exact(8)
+        .map(|pair| {
+            let freq = u32::from_le_bytes([pair[0], pair[1], pair[2], pair[3]]);
+            let voltage = u32::from_le_bytes([pair[4], pair[5], pair[6], pair[7]]);
+            VoltageState {
+                freq_mhz: if freq >= HZ_THRESHOLD {
+                    freq / 1_000_000
+                } else {
+                    freq / 1_000
+                },
+                voltage_mv: voltage,
+            }
+        })
+        .collect()
+}
+
+/// P-state frequencies in MHz, lowest first, without the zero-frequency
+/// placeholder entries some tables start with.
+pub fn frequencies_mhz(blob: &[u8]) -> Vec<u32> {
+    decode_voltage_states(blob)
+        .into_iter()
+        .map(|state| state.freq_mhz)
+        .filter(|&mhz| mhz > 0)
+        .collect()
+}
+
+/// Frequency of the `idx`-th of `count` active P-states of a residency
+/// channel. The channel and the table are aligned at the top, because some
+/// tables carry extra low entries the channel never reports. 0 if the table
+/// has no entry for the state.
+pub fn state_frequency(idx: usize, count: usize, freqs_mhz: &[u32]) -> u32 {
+    (idx + freqs_mhz.len())
+        .checked_sub(count)
+        .and_then(|table_idx| freqs_mhz.get(table_idx))
+        .copied()
+        .unwrap_or(0)
+}
+
+/// Residency-weighted average frequency and the share of `total` spent at
+/// each frequency, lowest first, for a channel whose active P-states spent
+/// `active` (in channel order). States without a table entry count towards
+/// neither.
+pub fn frequency_residency(
+    active: &[i64],
+    total: i64,
+    freqs_mhz: &[u32],
+) -> (u32, Vec<FreqResidency>) {
+    let mut residency: Vec<FreqResidency> = Vec::new();
+    if total <= 0 {
+        return (0, residency);
+    }
+
+    for (idx, &time) in active.iter().enumerate() {
+        let freq_mhz = state_frequency(idx, active.len(), freqs_mhz);
+        if freq_mhz == 0 {
+            continue;
+        }
+        let pct = time.max(0) as f64 / total as f64 * 100.0;
+        match residency.iter_mut().find(|r| r.freq_mhz == freq_mhz) {
+            Some(entry) => entry.residency_pct += pct,
+            None => residency.push(FreqResidency {
+                freq_mhz,
+                residency_pct: pct,
+            }),
+        }
+    }
+    residency.sort_by_key(|r| r.freq_mhz);
+
+    let known_pct: f64 = residency.iter().map(|r| r.residency_pct).sum();
+    let weighted: f64 = residency
+        .iter()
+        .map(|r| r.freq_mhz as f64 * r.residency_pct)
+        .sum();
+    let average = if known_pct > 0.0 {
+        (weighted / known_pct).round() as u32
+    } else {
+        0
+    };
+
+    (average, residency)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const M1_ECPU: &[u8] = include_bytes!("../../../tests/fixtures/pmgr/m1_voltage_states1.bin");
+    const M1_PCPU: &[u8] = include_bytes!("../../../tests/fixtures/pmgr/m1_voltage_states5.bin");
+    const M1_GPU: &[u8] = include_bytes!("../../../tests/fixtures/pmgr/m1_voltage_states9.bin");
+    const M4_PCPU: &[u8] =
+        include_bytes!("../../../tests/fixtures/pmgr/m4_voltage_states5_sram.bin");
+
+    #[test]
+    fn decodes_hz_tables() {
+        let states = decode_voltage_states(M1_ECPU);
+
+        assert_eq!(states.len(), 5);
+        assert_eq!(
+            states[0],
+            VoltageState {
+                freq_mhz: 600,
+                voltage_mv: 568
+            }
+        );
+        assert_eq!(
+            states[4],
+            VoltageState {
+                freq_mhz: 2064,
+                voltage_mv: 837
+            }
+        );
+        assert_eq!(frequencies_mhz(M1_PCPU).len(), 15);
+        assert_eq!(frequencies_mhz(M1_PCPU).last(), Some(&3204));
+    }
+
+    #[test]
+    fn decodes_khz_tables() {
+        let freqs = frequencies_mhz(M4_PCPU);
+
+        assert_eq!(freqs.first(), Some(&1260));
+        assert_eq!(freqs.last(), Some(&4512));
+    }
+
+    #[test]
+    fn drops_zero_frequency_placeholders() {
+        let states = decode_voltage_states(M1_GPU);
+        assert_eq!(states[0].freq_mhz, 0);
+
+        assert_eq!(frequencies_mhz(M1_GPU), [396, 528, 720, 924, 1128, 1278]);
+    }
+
+    #[test]
+    fn ignores_trailing_partial_pair() {
+        assert_eq!(decode_voltage_states(&M1_ECPU[..12]).len(), 1);
+        assert!(decode_voltage_states(&[]).is_empty());
+    }
+
+    #[test]
+    fn aligns_states_with_the_top_of_the_table() {
+        let freqs = frequencies_mhz(M1_ECPU);
+
+        // Five states, five entries
+        assert_eq!(state_frequency(0, 5, &freqs), 600);
+        assert_eq!(state_frequency(4, 5, &freqs), 2064);
+        // Four states: the lowest table entry is never reported
+        assert_eq!(state_frequency(0, 4, &freqs), 972);
+        // More states than entries: the extra low states are unknown
+        assert_eq!(state_frequency(0, 6, &freqs), 0);
+        assert_eq!(state_frequency(5, 6, &freqs), 2064);
+    }
+
+    #[test]
+    fn weights_frequency_by_residency() {
+        let freqs = frequencies_mhz(M1_ECPU);
+        // 1000 ticks: 500 idle, then 300 at 600 MHz and 200 at 2064 MHz
+        let (average, residency) = frequency_residency(&[300, 0, 0, 0, 200], 1000, &freqs);
+
+        // (600 * 300 + 2064 * 200) / 500
+        assert_eq!(average, 1186);
+        assert_eq!(residency.len(), 5);
+        assert_eq!(residency[0].freq_mhz, 600);
+        assert!((residency[0].residency_pct - 30.0).abs() < 1e-9);
+        assert!((residency[4].residency_pct - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn unknown_table_yields_no_frequency() {
+        let (average, residency) = frequency_residency(&[300, 200], 1000, &[]);
+
+        assert_eq!(average, 0);
+        assert!(residency.is_empty());
+    }
+}
@@ -0,0 +1,413 @@
+#![cfg_attr(not(target_os = "macos"), allow(dead_code))]
+
+use super::channel::IOReportSample;
+use crate::types::PowerNode;
+use std::ops::AddAssign;
+
+/// Unit of an "Energy Model" channel, from `IOReportChannelUnit`. Older
+/// chips report millijoules, newer ones mostly nanojoules, sometimes mixed
+/// within one sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnergyUnit {
+    Millijoules,
+    Microjoules,
+    Nanojoules,
+}
+
+impl EnergyUnit {
+    pub fn parse(unit: &str) -> Option<Self> {
+        match unit.trim() {
+            "mJ" => Some(EnergyUnit::Millijoules),
+            "uJ" | "µJ" | "μJ" => Some(EnergyUnit::Microjoules),
+            "nJ" => Some(EnergyUnit::Nanojoules),
+            _ => None,
+        }
+    }
+
+    pub fn to_joules(self, value: f64) -> f64 {
+        match self {
+            EnergyUnit::Millijoules => value / 1e3,
+            EnergyUnit::Microjoules => value / 1e6,
+            EnergyUnit::Nanojoules => value / 1e9,
+        }
+    }
+}
+
+/// Energy spent over one interval and the average power it implies.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EnergyReading {
+    pub joules: f64,
+    pub watts: f64,
+}
+
+impl EnergyReading {
+    /// Convert a channel delta. `None` if the unit isn't an energy unit or
+    /// the interval is empty.
+    pub fn from_delta(delta: i64, unit: &str, interval_secs: f64) -> Option<Self> {
+        let unit = EnergyUnit::parse(unit)?;
+        if interval_secs <= 0.0 {
+            return None;
+        }
+
+        // A counter reset shows up as a negative delta; treat it as no energy
+        let joules = unit.to_joules(delta.max(0) as f64);
+        Some(Self {
+            joules,
+            watts: joules / interval_secs,
+        })
+    }
+}
+
+impl AddAssign for EnergyReading {
+    fn add_assign(&mut self, other: Self) {
+        self.joules += other.joules;
+        self.watts += other.watts;
+    }
+}
+
+/// Build the component tree from "Energy Model" channels.
+///
+/// Channel names encode the hierarchy: a per-core channel is its cluster's
+/// name plus an index (`PACC0_CPU` -> `PACC0_CPU1`, `PCPU` -> `PCPU3`,
+/// `GPU Energy` -> `GPU0`), and clusters roll up into a `<X> Energy`
+/// aggregate whose `X` they contain (`EACC_CPU` -> `CPU Energy`). Anything
+/// else (ISP, AVE, DISP, AMCC, PCIe, ...) is a top-level leaf.
+pub fn power_tree(samples: &[IOReportSample], interval_secs: f64) -> Vec<PowerNode> {
+    let mut readings: Vec<(&str, EnergyReading)> = Vec::new();
+    for sample in samples.iter().filter(|s| s.group == "Energy Model") {
+        let Some(reading) = sample
+            .data
+            .simple()
+            .and_then(|value| EnergyReading::from_delta(value, &sample.unit, interval_secs))
+        else {
+            continue;
+        };
+        match readings
+            .iter_mut()
+            .find(|(name, _)| *name == sample.channel)
+        {
+            Some((_, total)) => *total += reading,
+            None => readings.push((&sample.channel, reading)),
+        }
+    }
+
+    let names: Vec<&str> = readings.iter().map(|(name, _)| *name).collect();
+    let parents: Vec<Option<usize>> = names.iter().map(|name| parent_of(name, &names)).collect();
+
+    (0..readings.len())
+        .filter(|&idx| parents[idx].is_none())
+        .map(|idx| build_node(idx, &readings, &parents))
+        .collect()
+}
+
+fn parent_of(name: &str, names: &[&str]) -> Option<usize> {
+    let find = |candidate: &str| names.iter().position(|n| *n == candidate);
+
+    let stem = name
+        .trim_end_matches(|c: char| c.is_ascii_digit())
+        .trim_end_matches(['_', ' ']);
+    if !stem.is_empty() && stem != name {
+        if let Some(idx) = find(stem).or_else(|| find(&format!("{} Energy", stem))) {
+            return Some(idx);
+        }
+    }
+
+    if name.ends_with(" Energy") {
+        return None;
+    }
+    names.iter().position(|aggregate| {
+        aggregate
+            .strip_suffix(" Energy")
+            .is_some_and(|kind| !kind.is_empty() && name.contains(kind))
+    })
+}
+
+fn build_node(
+    idx: usize,
+    readings: &[(&str, EnergyReading)],
+    parents: &[Option<usize>],
+) -> PowerNode {
+    let (name, own) = readings[idx];
+    let children = (0..readings.len())
+        .filter(|&child| parents[child] == Some(idx))
+        .map(|child| build_node(child, readings, parents))
+        .collect();
+    PowerNode::parent(name, Some((own.watts, own.joules)), children)
+}
+
+/// The power tree bucketed into the `SystemMetrics` power fields. Only
+/// top-level nodes are summed, so each leaf is counted once.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EnergyBreakdown {
+    pub cpu: EnergyReading,
+    pub gpu: EnergyReading,
+    pub ane: EnergyReading,
+    pub dram: EnergyReading,
+    /// Everything else: media engines, display, fabric, I/O
+    pub other: EnergyReading,
+}
+
+impl EnergyBreakdown {
+    pub fn from_tree(tree: &[PowerNode]) -> Self {
+        let mut breakdown = Self::default();
+
+        for node in tree {
+            let reading = EnergyReading {
+                joules: node.joules,
+                watts: node.watts,
+            };
+            if node.name.contains("CPU") {
+                breakdown.cpu += reading;
+            } else if node.name.contains("GPU") {
+                breakdown.gpu += reading;
+            } else if node.name.contains("ANE") {
+                breakdown.ane += reading;
+            } else if node.name.contains("DRAM") {
+                breakdown.dram += reading;
+            } else {
+                breakdown.other += reading;
+            }
+        }
+
+        breakdown
+    }
+
+    pub fn total(&self) -> EnergyReading {
+        let mut total = self.cpu;
+        total += self.gpu;
+        total += self.ane;
+        total += self.dram;
+        total += self.other;
+        total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::ioreport::channel::IOReportFixture;
+    use crate::test_util::assert_close;
+    use crate::types::IOReportChannel;
+
+    fn fixture(json: &str) -> (Vec<IOReportSample>, f64) {
+        let fixture = IOReportFixture::from_json(json).unwrap();
+        let interval = fixture.interval_secs();
+        (fixture.samples, interval)
+    }
+
+    #[test]
+    fn parses_energy_units() {
+        assert_eq!(EnergyUnit::parse("mJ"), Some(EnergyUnit::Millijoules));
+        assert_eq!(EnergyUnit::parse("uJ"), Some(EnergyUnit::Microjoules));
+        assert_eq!(EnergyUnit::parse("µJ"), Some(EnergyUnit::Microjoules));
+        assert_eq!(EnergyUnit::parse(" nJ "), Some(EnergyUnit::Nanojoules));
+        assert_eq!(EnergyUnit::parse("24Mticks"), None);
+        assert_eq!(EnergyUnit::parse(""), None);
+    }
+
+    #[test]
+    fn converts_delta_to_joules_and_watts() {
+        let reading = EnergyReading::from_delta(1500, "mJ", 1.5).unwrap();
+        assert_close(reading.joules, 1.5);
+        assert_close(reading.watts, 1.0);
+
+        let reading = EnergyReading::from_delta(2_000_000_000, "nJ", 0.5).unwrap();
+        assert_close(reading.joules, 2.0);
+        assert_close(reading.watts, 4.0);
+    }
+
+    #[test]
+    fn rejects_unknown_units_and_empty_intervals() {
+        assert_eq!(EnergyReading::from_delta(100, "24Mticks", 1.0), None);
+        assert_eq!(EnergyReading::from_delta(100, "mJ", 0.0), None);
+    }
+
+    #[test]
+    fn negative_delta_is_no_energy() {
+        let reading = EnergyReading::from_delta(-42, "mJ", 1.0).unwrap();
+        assert_eq!(reading, EnergyReading::default());
+    }
+
+    #[test]
+    fn millijoule_fixture() {
+        let (samples, interval) = fixture(include_str!(
+            "../../../tests/fixtures/ioreport/energy_model_mj.json"
+        ));
+        let breakdown = EnergyBreakdown::from_tree(&power_tree(&samples, interval));
+
+        assert_close(breakdown.cpu.watts, 1.834);
+        assert_close(breakdown.gpu.watts, 0.412);
+        assert_close(breakdown.ane.watts, 0.0);
+        assert_close(breakdown.dram.watts, 0.287);
+        assert_close(breakdown.total().watts, 2.533);
+        assert_close(breakdown.total().joules, 2.533);
+    }
+
+    #[test]
+    fn nanojoule_fixture_with_mixed_units() {
+        let (samples, interval) = fixture(include_str!(
+            "../../../tests/fixtures/ioreport/energy_model_nj.json"
+        ));
+        let breakdown = EnergyBreakdown::from_tree(&power_tree(&samples, interval));
+
+        // 2 s interval: joules are the raw energy, watts half of it
+        assert_close(breakdown.cpu.joules, 5.12);
+        assert_close(breakdown.cpu.watts, 2.56);
+        assert_close(breakdown.gpu.watts, 0.92);
+        assert_close(breakdown.ane.watts, 0.006);
+        // DRAM is reported in mJ on the same chip
+        assert_close(breakdown.dram.joules, 0.61);
+        assert_close(breakdown.dram.watts, 0.305);
+    }
+
+    #[test]
+    fn power_does_not_scale_with_interval() {
+        let (samples, _) = fixture(include_str!(
+            "../../../tests/fixtures/ioreport/energy_model_mj.json"
+        ));
+        // Twice the energy over twice the time is the same power
+        let doubled: Vec<IOReportSample> = samples
+            .iter()
+            .cloned()
+            .map(|mut s| {
+                if let IOReportChannel::Simple { value } = &mut s.data {
+                    *value *= 2;
+                }
+                s
+            })
+            .collect();
+
+        let one = EnergyBreakdown::from_tree(&power_tree(&samples, 1.0));
+        let two = EnergyBreakdown::from_tree(&power_tree(&doubled, 2.0));
+        assert_close(one.total().watts, two.total().watts);
+        assert_close(two.total().joules, 2.0 * one.total().joules);
+    }
+
+    fn find<'a>(nodes: &'a [PowerNode], name: &str) -> &'a PowerNode {
+        nodes
+            .iter()
+            .find(|n| n.name == name)
+            .unwrap_or_else(|| panic!("no node named {}", name))
+    }
+
+    fn child_names(node: &PowerNode) -> Vec<&str> {
+        node.children.iter().map(|c| c.name.as_str()).collect()
+    }
+
+    fn leaf_watts(nodes: &[PowerNode]) -> f64 {
+        nodes
+            .iter()
+            .map(|n| {
+                if n.children.is_empty() {
+                    n.watts
+                } else {
+                    leaf_watts(&n.children)
+                }
+            })
+            .sum()
+    }
+
+    fn tree_fixture() -> Vec<PowerNode> {
+        let (samples, interval) = fixture(include_str!(
+            "../../../tests/fixtures/ioreport/energy_model_tree.json"
+        ));
+        power_tree(&samples, interval)
+    }
+
+    #[test]
+    fn cores_and_clusters_are_not_double_counted() {
+        let tree = tree_fixture();
+        let breakdown = EnergyBreakdown::from_tree(&tree);
+
+        // Only "CPU Energy", not its clusters and cores on top of it
+        assert_close(breakdown.cpu.watts, 2.0);
+        assert_close(breakdown.gpu.watts, 0.9);
+        assert_close(breakdown.ane.watts, 0.03);
+        assert_close(breakdown.dram.watts, 0.45);
+        assert_close(breakdown.other.watts, 0.45);
+        assert_close(breakdown.total().watts, 3.83);
+        assert_close(leaf_watts(&tree), breakdown.total().watts);
+    }
+
+    #[test]
+    fn builds_soc_cluster_core_hierarchy() {
+        let tree = tree_fixture();
+
+        let cpu = find(&tree, "CPU Energy");
+        assert_eq!(
+            child_names(cpu),
+            ["EACC_CPU", "PACC0_CPU", "PACC1_CPU", PowerNode::REMAINDER]
+        );
+        assert_close(find(&cpu.children, PowerNode::REMAINDER).watts, 0.1);
+
+        // Cluster overhead beyond its cores becomes an "other" leaf
+        let ecluster = find(&cpu.children, "EACC_CPU");
+        assert_eq!(
+            child_names(ecluster),
+            ["EACC_CPU0", "EACC_CPU1", PowerNode::REMAINDER]
+        );
+        assert_close(find(&ecluster.children, PowerNode::REMAINDER).watts, 0.15);
+
+        // A cluster that is exactly its cores gets no remainder
+        let pcluster = find(&cpu.children, "PACC0_CPU");
+        assert_eq!(
+            child_names(pcluster),
+            ["PACC0_CPU0", "PACC0_CPU1", "PACC0_CPU2", "PACC0_CPU3"]
+        );
+
+        let gpu = find(&tree, "GPU Energy");
+        assert_eq!(
+            child_names(gpu),
+            ["GPU0", "GPU SRAM0", PowerNode::REMAINDER]
+        );
+    }
+
+    #[test]
+    fn keeps_every_other_agent() {
+        let tree = tree_fixture();
+        let top: Vec<&str> = tree.iter().map(|n| n.name.as_str()).collect();
+
+        assert_eq!(
+            top,
+            [
+                "CPU Energy",
+                "GPU Energy",
+                "ANE0",
+                "DRAM0",
+                "ISP",
+                "AVE0",
+                "MSR",
+                "AMCC",
+                "DCS",
+                "DISP",
+                "DISPEXT",
+                "PCIe Port 0"
+            ]
+        );
+        assert_close(find(&tree, "DISP").watts, 0.2);
+    }
+
+    #[test]
+    fn clusters_without_aggregate_are_top_level() {
+        let channel = |name: &str, value| IOReportSample {
+            group: "Energy Model".into(),
+            subgroup: String::new(),
+            channel: name.into(),
+            unit: "mJ".into(),
+            data: IOReportChannel::Simple { value },
+        };
+        let samples = [
+            channel("ECPU0", 40),
+            channel("ECPU1", 60),
+            channel("ECPU", 100),
+            channel("PCPU0", 300),
+            channel("PCPU1", 200),
+            channel("PCPU", 500),
+        ];
+        let tree = power_tree(&samples, 1.0);
+
+        assert_eq!(tree.len(), 2);
+        assert_eq!(child_names(&tree[0]), ["ECPU0", "ECPU1"]);
+        assert_close(EnergyBreakdown::from_tree(&tree).cpu.watts, 0.6);
+    }
+}
@@ -0,0 +1,420 @@
+use core_foundation::base::{CFTypeRef, TCFType};
+use core_foundation::dictionary::CFDictionaryRef;
+use core_foundation::array::CFArrayRef;
+use core_foundation::string::{CFString, CFStringRef};
+use core_foundation::number::CFNumberRef;
+use core_foundation_sys::base::{CFGetTypeID, CFRelease};
+use core_foundation_sys::string::CFStringGetTypeID;
+use std::ffi::c_void;
+use std::ptr;
+
+use super::channel::IOReportSample;
+use crate::types::{
+    HistogramBucket, IOReportChannel, IOReportChannelInfo, IOReportFormat, StateResidency,
+};
+
+type IOReportSubscriptionRef = *mut c_void;
+
+#[link(name = "IOReport", kind = "dylib")]
+extern "C" {
+    fn IOReportCopyChannelsInGroup(
+        group: CFStringRef,
+        subgroup: CFStringRef,
+        a: u64,
+        b: u64,
+        c: u64,
+    ) -> CFDictionaryRef;
+    fn IOReportCopyAllChannels(a: u64, b: u64) -> CFDictionaryRef;
+    fn IOReportMergeChannels(a: CFDictionaryRef, b: CFDictionaryRef, nil: CFTypeRef);
+    fn IOReportCreateSubscription(
+        a: *const c_void,
+        channels: CFDictionaryRef,
+        b: *mut CFDictionaryRef,
+        c: u64,
+        d: CFTypeRef,
+    ) -> IOReportSubscriptionRef;
+    fn IOReportCreateSamples(
+        sub: IOReportSubscriptionRef,
+        a: CFDictionaryRef,
+        b: CFTypeRef,
+    ) -> CFDictionaryRef;
+    fn IOReportCreateSamplesDelta(
+        a: CFDictionaryRef,
+        b: CFDictionaryRef,
+        c: CFTypeRef,
+    ) -> CFDictionaryRef;
+    fn IOReportChannelGetFormat(channel: CFDictionaryRef) -> u8;
+    fn IOReportChannelGetUnitLabel(channel: CFDictionaryRef) -> CFStringRef;
+    fn IOReportStateGetCount(channel: CFDictionaryRef) -> i32;
+    fn IOReportStateGetNameForIndex(channel: CFDictionaryRef, index: i32) -> CFStringRef;
+    fn IOReportStateGetResidency(channel: CFDictionaryRef, index: i32) -> i64;
+    fn IOReportHistogramGetBucketCount(channel: CFDictionaryRef) -> i32;
+    fn IOReportHistogramGetBucketMinValue(channel: CFDictionaryRef, index: i32) -> i64;
+    fn IOReportHistogramGetBucketMaxValue(channel: CFDictionaryRef, index: i32) -> i64;
+    fn IOReportHistogramGetBucketHits(channel: CFDictionaryRef, index: i32) -> i64;
+}
+
+// CFDictionary helper functions
+extern "C" {
+    fn CFDictionaryGetValue(dict: CFDictionaryRef, key: *const c_void) -> *const c_void;
+    fn CFArrayGetCount(array: CFArrayRef) -> isize;
+    fn CFArrayGetValueAtIndex(array: CFArrayRef, idx: isize) -> *const c_void;
+    fn CFStringGetCStringPtr(string: CFStringRef, encoding: u32) -> *const i8;
+    fn CFStringGetCString(
+        string: CFStringRef,
+        buffer: *mut i8,
+        buffer_size: isize,
+        encoding: u32,
+    ) -> bool;
+    fn CFNumberGetValue(number: CFNumberRef, number_type: i32, value_ptr: *mut c_void) -> bool;
+}
+
+const IOREPORT_PATH: &str = "/System/Library/PrivateFrameworks/IOReport.framework/IOReport";
+const K_CF_STRING_ENCODING_UTF8: u32 = 0x08000100;
+const K_CF_NUMBER_SINT64_TYPE: i32 = 4;
+const K_IOREPORT_FORMAT_SIMPLE: u8 = 1;
+const K_IOREPORT_FORMAT_STATE: u8 = 2;
+const K_IOREPORT_FORMAT_HISTOGRAM: u8 = 3;
+
+pub struct IOReport {
+    subscription: IOReportSubscriptionRef,
+    channels: CFDictionaryRef,
+    prev_sample: CFDictionaryRef,
+}
+
+unsafe impl Send for IOReport {}
+
+impl IOReport {
+    pub fn new(channel_groups: &[(&str, Option<&str>)]) -> Result<Self, String> {
+        unsafe {
+            Self::load_framework()?;
+            let merged_channels = Self::copy_channels(channel_groups);
+            if merged_channels.is_null() {
+                return Err("No valid channels found".into());
+            }
+
+            Self::subscribe(merged_channels)
+        }
+    }
+
+    /// Subscribe to every channel the system advertises.
+    pub fn all() -> Result<Self, String> {
+        unsafe {
+            Self::load_framework()?;
+            let channels = IOReportCopyAllChannels(0, 0);
+            if channels.is_null() {
+                return Err("No valid channels found".into());
+            }
+
+            Self::subscribe(channels)
+        }
+    }
+
+    /// Every advertised channel, or those of `group`, without sampling them.
+    pub fn list_channels(group: Option<&str>) -> Result<Vec<IOReportChannelInfo>, String> {
+        unsafe {
+            Self::load_framework()?;
+            let channels = match group {
+                Some(group) => Self::copy_channels(&[(group, None)]),
+                None => IOReportCopyAllChannels(0, 0),
+            };
+            if channels.is_null() {
+                return Ok(Vec::new());
+            }
+
+            let mut infos: Vec<IOReportChannelInfo> = Self::channel_dicts(channels)
+                .into_iter()
+                .map(Self::channel_info)
+                .collect();
+            CFRelease(channels as *const c_void);
+
+            infos.sort_by(|a, b| {
+                (&a.group, &a.subgroup, &a.channel).cmp(&(&b.group, &b.subgroup, &b.channel))
+            });
+            Ok(infos)
+        }
+    }
+
+    unsafe fn load_framework() -> Result<(), String> {
+        // Load the IOReport framework dynamically
+        let path = std::ffi::CString::new(IOREPORT_PATH).unwrap();
+        let handle = libc::dlopen(path.as_ptr(), libc::RTLD_NOW);
+        if handle.is_null() {
+            return Err("Failed to load IOReport framework".into());
+        }
+        Ok(())
+    }
+
+    /// The channels of `channel_groups` merged into one dictionary; null if
+    /// none of the groups exist.
+    unsafe fn copy_channels(channel_groups: &[(&str, Option<&str>)]) -> CFDictionaryRef {
+        let mut merged_channels: CFDictionaryRef = ptr::null();
+
+        for (group, subgroup) in channel_groups {
+            let group_cf = CFString::new(group);
+            let subgroup_cf = subgroup.map(CFString::new);
+
+            let channels_dict = IOReportCopyChannelsInGroup(
+                group_cf.as_concrete_TypeRef(),
+                subgroup_cf
+                    .as_ref()
+                    .map(|s| s.as_concrete_TypeRef())
+                    .unwrap_or(ptr::null()),
+                0,
+                0,
+                0,
+            );
+
+            if channels_dict.is_null() {
+                continue;
+            }
+
+            if merged_channels.is_null() {
+                merged_channels = channels_dict;
+            } else {
+                IOReportMergeChannels(merged_channels, channels_dict, ptr::null());
+                CFRelease(channels_dict as *const c_void);
+            }
+        }
+
+        merged_channels
+    }
+
+    /// Takes ownership of `channels`.
+    unsafe fn subscribe(channels: CFDictionaryRef) -> Result<Self, String> {
+        let mut sub_dict: CFDictionaryRef = ptr::null();
+        let subscription =
+            IOReportCreateSubscription(ptr::null(), channels, &mut sub_dict, 0, ptr::null());
+
+        if subscription.is_null() {
+            CFRelease(channels as *const c_void);
+            return Err("Failed to create IOReport subscription".into());
+        }
+
+        Ok(Self {
+            subscription,
+            channels,
+            prev_sample: ptr::null(),
+        })
+    }
+
+    pub fn get_sample(&mut self) -> Result<Vec<IOReportSample>, String> {
+        unsafe {
+            let sample_dict = IOReportCreateSamples(
+                self.subscription,
+                self.channels,
+                ptr::null(),
+            );
+
+            if sample_dict.is_null() {
+                return Err("IOReportCreateSamples returned no data".into());
+            }
+
+            let result = if !self.prev_sample.is_null() {
+                let delta_dict = IOReportCreateSamplesDelta(
+                    self.prev_sample,
+                    sample_dict,
+                    ptr::null(),
+                );
+
+                if delta_dict.is_null() {
+                    vec![]
+                } else {
+                    let samples = Self::parse_samples(delta_dict);
+                    CFRelease(delta_dict as *const c_void);
+                    samples
+                }
+            } else {
+                vec![]
+            };
+
+            // Release previous sample and store new one
+            if !self.prev_sample.is_null() {
+                CFRelease(self.prev_sample as *const c_void);
+            }
+            self.prev_sample = sample_dict;
+
+            Ok(result)
+        }
+    }
+
+    fn parse_samples(dict: CFDictionaryRef) -> Vec<IOReportSample> {
+        Self::channel_dicts(dict)
+            .into_iter()
+            .filter_map(Self::parse_channel)
+            .collect()
+    }
+
+    /// The entries of a channel or sample dictionary's `IOReportChannels`
+    /// array, borrowed from `dict`.
+    fn channel_dicts(dict: CFDictionaryRef) -> Vec<CFDictionaryRef> {
+        unsafe {
+            let channels_key = CFString::new("IOReportChannels");
+            let channels_array =
+                CFDictionaryGetValue(dict, channels_key.as_concrete_TypeRef() as *const c_void)
+                    as CFArrayRef;
+
+            if channels_array.is_null() {
+                return Vec::new();
+            }
+
+            (0..CFArrayGetCount(channels_array))
+                .map(|i| CFArrayGetValueAtIndex(channels_array, i) as CFDictionaryRef)
+                .filter(|channel| !channel.is_null())
+                .collect()
+        }
+    }
+
+    fn channel_info(dict: CFDictionaryRef) -> IOReportChannelInfo {
+        IOReportChannelInfo {
+            group: Self::get_string(dict, "IOReportGroupName").unwrap_or_default(),
+            subgroup: Self::get_string(dict, "IOReportSubGroupName").unwrap_or_default(),
+            channel: Self::get_string(dict, "IOReportChannelName").unwrap_or_default(),
+            format: match unsafe { IOReportChannelGetFormat(dict) } {
+                K_IOREPORT_FORMAT_SIMPLE => IOReportFormat::Simple,
+                K_IOREPORT_FORMAT_STATE => IOReportFormat::State,
+                K_IOREPORT_FORMAT_HISTOGRAM => IOReportFormat::Histogram,
+                _ => IOReportFormat::Unknown,
+            },
+            unit: Self::unit_label(dict),
+        }
+    }
+
+    fn parse_channel(dict: CFDictionaryRef) -> Option<IOReportSample> {
+        let group = Self::get_string(dict, "IOReportGroupName").unwrap_or_default();
+        let subgroup = Self::get_string(dict, "IOReportSubGroupName").unwrap_or_default();
+        let channel_name = Self::get_string(dict, "IOReportChannelName").unwrap_or_default();
+        let unit = Self::unit_label(dict);
+        let data = match unsafe { IOReportChannelGetFormat(dict) } {
+            K_IOREPORT_FORMAT_STATE => IOReportChannel::State {
+                states: Self::get_states(dict),
+            },
+            K_IOREPORT_FORMAT_HISTOGRAM => IOReportChannel::Histogram {
+                buckets: Self::get_buckets(dict),
+            },
+            _ => IOReportChannel::Simple {
+                value: Self::get_value(dict),
+            },
+        };
+
+        Some(IOReportSample {
+            group,
+            subgroup,
+            channel: channel_name,
+            unit,
+            data,
+        })
+    }
+
+    fn get_states(dict: CFDictionaryRef) -> Vec<StateResidency> {
+        unsafe {
+            (0..IOReportStateGetCount(dict))
+                .map(|index| StateResidency {
+                    name: Self::cf_string(IOReportStateGetNameForIndex(dict, index))
+                        .unwrap_or_default(),
+                    residency: IOReportStateGetResidency(dict, index),
+                })
+                .collect()
+        }
+    }
+
+    fn get_buckets(dict: CFDictionaryRef) -> Vec<HistogramBucket> {
+        unsafe {
+            (0..IOReportHistogramGetBucketCount(dict))
+                .map(|index| HistogramBucket {
+                    min: IOReportHistogramGetBucketMinValue(dict, index),
+                    max: IOReportHistogramGetBucketMaxValue(dict, index),
+                    hits: IOReportHistogramGetBucketHits(dict, index),
+                })
+                .collect()
+        }
+    }
+
+    /// The channel's unit as text ("mJ", "nJ", ...). `IOReportChannelUnit`
+    /// itself holds the encoded unit as a number.
+    fn unit_label(dict: CFDictionaryRef) -> String {
+        unsafe { Self::cf_string(IOReportChannelGetUnitLabel(dict)) }
+            .unwrap_or_default()
+            .trim()
+            .to_string()
+    }
+
+    fn get_string(dict: CFDictionaryRef, key: &str) -> Option<String> {
+        unsafe {
+            let key_cf = CFString::new(key);
+            let value = CFDictionaryGetValue(dict, key_cf.as_concrete_TypeRef() as *const c_void);
+            if value.is_null() || CFGetTypeID(value) != CFStringGetTypeID() {
+                return None;
+            }
+
+            Self::cf_string(value as CFStringRef)
+        }
+    }
+
+    fn cf_string(value: CFStringRef) -> Option<String> {
+        unsafe {
+            if value.is_null() {
+                return None;
+            }
+
+            // Try fast path first
+            let cstr = CFStringGetCStringPtr(value, K_CF_STRING_ENCODING_UTF8);
+            if !cstr.is_null() {
+                return Some(
+                    std::ffi::CStr::from_ptr(cstr)
+                        .to_string_lossy()
+                        .to_string(),
+                );
+            }
+
+            // Fallback: copy to buffer
+            let mut buffer = [0i8; 256];
+            if CFStringGetCString(
+                value,
+                buffer.as_mut_ptr(),
+                buffer.len() as isize,
+                K_CF_STRING_ENCODING_UTF8,
+            ) {
+                Some(
+                    std::ffi::CStr::from_ptr(buffer.as_ptr())
+                        .to_string_lossy()
+                        .to_string(),
+                )
+            } else {
+                None
+            }
+        }
+    }
+
+    fn get_value(dict: CFDictionaryRef) -> i64 {
+        unsafe {
+            // First try simple value
+            let key = CFString::new("IOReportSimpleValue");
+            let num = CFDictionaryGetValue(dict, key.as_concrete_TypeRef() as *const c_void)
+                as CFNumberRef;
+
+            if !num.is_null() {
+                let mut value: i64 = 0;
+                if CFNumberGetValue(num, K_CF_NUMBER_SINT64_TYPE, &mut value as *mut _ as *mut c_void)
+                {
+                    return value;
+                }
+            }
+
+            0
+        }
+    }
+}
+
+impl Drop for IOReport {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.channels.is_null() {
+                CFRelease(self.channels as *const c_void);
+            }
+            if !self.prev_sample.is_null() {
+                CFRelease(self.prev_sample as *const c_void);
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+#![cfg_attr(not(target_os = "macos"), allow(dead_code))]
+
+use super::channel::IOReportSample;
+use super::dvfs::state_frequency;
+use crate::types::{GpuMetrics, PStateResidency, StateResidency};
+
+const GPU_STATS: &str = "GPU Stats";
+const GPU_PERF_STATES: &str = "GPU Performance States";
+/// The GPU's own power-state channel; the subgroup also carries per-block
+/// channels on some chips
+const GPU_PSTATE_CHANNEL: &str = "GPUPH";
+
+/// Active %, average frequency and P-state breakdown from the GPU
+/// performance-state channel. `freqs_mhz` is the chip's GPU DVFS table,
+/// lowest first; without it frequencies are left at 0. `power_watts` is not
+/// set here. Returns `None` if the channel is missing or reports no time.
+pub fn gpu_metrics(samples: &[IOReportSample], freqs_mhz: &[u32]) -> Option<GpuMetrics> {
+    let perf_states = samples.iter().filter(|s| {
+        s.group == GPU_STATS && s.subgroup == GPU_PERF_STATES && !s.data.states().is_empty()
+    });
+    let channel = perf_states
+        .clone()
+        .find(|s| s.channel == GPU_PSTATE_CHANNEL)
+        .or_else(|| perf_states.clone().next())?;
+
+    let states = channel.data.states();
+    let total: i64 = states.iter().map(|s| s.residency.max(0)).sum();
+    if total == 0 {
+        return None;
+    }
+
+    let active: Vec<&StateResidency> = states.iter().filter(|s| s.is_active()).collect();
+    let pstate_residency: Vec<PStateResidency> = active
+        .iter()
+        .enumerate()
+        .map(|(idx, state)| PStateResidency {
+            state: state.name.clone(),
+            freq_mhz: state_frequency(idx, active.len(), freqs_mhz),
+            residency_pct: state.residency.max(0) as f64 / total as f64 * 100.0,
+        })
+        .collect();
+
+    let active_pct: f64 = pstate_residency.iter().map(|p| p.residency_pct).sum();
+    // States without a known frequency add nothing to either sum
+    let weighted: f64 = pstate_residency
+        .iter()
+        .map(|p| p.freq_mhz as f64 * p.residency_pct)
+        .sum();
+    let known_pct: f64 = pstate_residency
+        .iter()
+        .filter(|p| p.freq_mhz > 0)
+        .map(|p| p.residency_pct)
+        .sum();
+    let freq_mhz = if known_pct > 0.0 {
+        (weighted / known_pct).round() as u32
+    } else {
+        0
+    };
+
+    Some(GpuMetrics {
+        freq_mhz,
+        freq_max_mhz: freqs_mhz.iter().copied().max().unwrap_or(0),
+        active_pct: active_pct.min(100.0),
+        power_watts: 0.0,
+        pstate_residency,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::ioreport::channel::IOReportFixture;
+    use crate::test_util::assert_close;
+    use crate::types::IOReportChannel;
+
+    /// GPU DVFS table of the fixture's chip, lowest first
+    const FREQS_MHZ: &[u32] = &[396, 444, 612, 808];
+
+    fn fixture() -> Vec<IOReportSample> {
+        IOReportFixture::from_json(include_str!(
+            "../../../tests/fixtures/ioreport/gpu_performance_states.json"
+        ))
+        .unwrap()
+        .samples
+    }
+
+    #[test]
+    fn active_pct_excludes_off_and_idle() {
+        let gpu = gpu_metrics(&fixture(), FREQS_MHZ).unwrap();
+
+        // 60 + 20 + 10 of 200 ticks
+        assert_close(gpu.active_pct, 45.0);
+        let states: Vec<&str> = gpu
+            .pstate_residency
+            .iter()
+            .map(|p| p.state.as_str())
+            .collect();
+        assert_eq!(states, ["P1", "P2", "P3"]);
+        assert_close(gpu.pstate_residency[0].residency_pct, 30.0);
+        assert_close(gpu.pstate_residency[2].residency_pct, 5.0);
+    }
+
+    #[test]
+    fn frequency_is_residency_weighted_and_top_aligned() {
+        let gpu = gpu_metrics(&fixture(), FREQS_MHZ).unwrap();
+
+        // The four-entry table's lowest entry has no matching state
+        let freqs: Vec<u32> = gpu.pstate_residency.iter().map(|p| p.freq_mhz).collect();
+        assert_eq!(freqs, [444, 612, 808]);
+        // (444 * 60 + 612 * 20 + 808 * 10) / 90
+        assert_eq!(gpu.freq_mhz, 522);
+        assert_eq!(gpu.freq_max_mhz, 808);
+    }
+
+    #[test]
+    fn missing_frequency_table_keeps_residency() {
+        let gpu = gpu_metrics(&fixture(), &[]).unwrap();
+
+        assert_close(gpu.active_pct, 45.0);
+        assert_eq!(gpu.freq_mhz, 0);
+        assert_eq!(gpu.freq_max_mhz, 0);
+        assert!(gpu.pstate_residency.iter().all(|p| p.freq_mhz == 0));
+    }
+
+    #[test]
+    fn missing_or_empty_channel_yields_none() {
+        assert!(gpu_metrics(&[], &[389, 486]).is_none());
+
+        let mut samples = fixture();
+        for sample in &mut samples {
+            if let IOReportChannel::State { states } = &mut sample.data {
+                for state in states {
+                    state.residency = 0;
+                }
+            }
+        }
+        assert!(gpu_metrics(&samples, FREQS_MHZ).is_none());
+    }
+}
@@ -0,0 +1,19 @@
+//! IOReport channel sampling. The CoreFoundation bindings are macOS-only;
+//! decoding and aggregation of the samples is plain Rust so it can be tested
+//! against recorded fixtures on any OS.
+
+mod bandwidth;
+mod channel;
+mod cpu;
+mod dvfs;
+mod energy;
+#[cfg(target_os = "macos")]
+mod ffi;
+mod gpu;
+#[cfg(target_os = "macos")]
+mod pmgr;
+#[cfg(target_os = "macos")]
+mod source;
+
+#[cfg(target_os = "macos")]
+pub use source::IOReportSource;
@@ -0,0 +1,128 @@
+use super::dvfs::{self, DvfsTables};
+use core_foundation::base::TCFType;
+use core_foundation::data::{CFData, CFDataRef};
+use core_foundation::dictionary::CFDictionaryRef;
+use core_foundation::string::CFString;
+use core_foundation_sys::base::CFRelease;
+use std::collections::HashMap;
+use std::ffi::c_void;
+
+type IOIterator = u32;
+type IOObject = u32;
+
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IOServiceMatching(name: *const i8) -> *const c_void;
+    fn IOServiceGetMatchingServices(
+        master_port: u32,
+        matching: *const c_void,
+        iterator: *mut IOIterator,
+    ) -> i32;
+    fn IOIteratorNext(iterator: IOIterator) -> IOObject;
+    fn IORegistryEntryGetName(entry: IOObject, name: *mut i8) -> i32;
+    fn IORegistryEntryCreateCFProperties(
+        entry: IOObject,
+        properties: *mut CFDictionaryRef,
+        allocator: *const c_void,
+        options: u32,
+    ) -> i32;
+    fn IOObjectRelease(object: IOObject) -> i32;
+}
+
+extern "C" {
+    fn CFDictionaryGetValue(dict: CFDictionaryRef, key: *const c_void) -> *const c_void;
+}
+
+/// DVFS tables in the power manager node: E cores, P cores, GPU. Newer chips
+/// only carry the `-sram` variants of the CPU tables.
+const ECPU_VOLTAGE_STATES: &[&str] = &["voltage-states1", "voltage-states1-sram"];
+const PCPU_VOLTAGE_STATES: &[&str] = &["voltage-states5", "voltage-states5-sram"];
+const GPU_VOLTAGE_STATES: &[&str] = &["voltage-states9"];
+
+/// CPU and GPU P-state frequencies from the `pmgr` node's voltage-states
+/// tables. Tables whose node or property is missing are left empty.
+pub fn dvfs_tables() -> DvfsTables {
+    let Some(props) = pmgr_properties() else {
+        return DvfsTables::default();
+    };
+
+    let table = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| props.get(*key))
+            .map(|blob| dvfs::frequencies_mhz(blob))
+            .unwrap_or_default()
+    };
+    DvfsTables {
+        ecpu_mhz: table(ECPU_VOLTAGE_STATES),
+        pcpu_mhz: table(PCPU_VOLTAGE_STATES),
+        gpu_mhz: table(GPU_VOLTAGE_STATES),
+    }
+}
+
+/// The `voltage-states*` data properties of the `pmgr` node.
+fn pmgr_properties() -> Option<HashMap<String, Vec<u8>>> {
+    let keys: Vec<&str> = ECPU_VOLTAGE_STATES
+        .iter()
+        .chain(PCPU_VOLTAGE_STATES)
+        .chain(GPU_VOLTAGE_STATES)
+        .copied()
+        .collect();
+
+    unsafe {
+        let matching = IOServiceMatching(c"AppleARMIODevice".as_ptr());
+        if matching.is_null() {
+            return None;
+        }
+
+        let mut iterator: IOIterator = 0;
+        if IOServiceGetMatchingServices(0, matching, &mut iterator) != 0 {
+            return None;
+        }
+
+        let mut result = None;
+        loop {
+            let entry = IOIteratorNext(iterator);
+            if entry == 0 {
+                break;
+            }
+
+            let mut name_buf = [0i8; 128];
+            let is_pmgr = IORegistryEntryGetName(entry, name_buf.as_mut_ptr()) == 0
+                && std::ffi::CStr::from_ptr(name_buf.as_ptr()).to_bytes() == b"pmgr";
+
+            if is_pmgr {
+                result = entry_data(entry, &keys);
+            }
+            IOObjectRelease(entry);
+
+            if is_pmgr {
+                break;
+            }
+        }
+
+        IOObjectRelease(iterator);
+        result
+    }
+}
+
+unsafe fn entry_data(entry: IOObject, keys: &[&str]) -> Option<HashMap<String, Vec<u8>>> {
+    let mut props: CFDictionaryRef = std::ptr::null();
+    if IORegistryEntryCreateCFProperties(entry, &mut props, std::ptr::null(), 0) != 0
+        || props.is_null()
+    {
+        return None;
+    }
+
+    let mut data = HashMap::new();
+    for &key in keys {
+        let key_cf = CFString::new(key);
+        let value = CFDictionaryGetValue(props, key_cf.as_concrete_TypeRef() as *const c_void);
+        if !value.is_null() {
+            let bytes = CFData::wrap_under_get_rule(value as CFDataRef);
+            data.insert(key.to_string(), bytes.bytes().to_vec());
+        }
+    }
+
+    CFRelease(props as *const c_void);
+    Some(data)
+}
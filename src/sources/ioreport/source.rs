@@ -0,0 +1,144 @@
+use super::bandwidth::memory_bandwidth;
+use super::channel::{subscription_groups, ChannelSpec, IOReportFixture, CHANNEL_GROUPS};
+use super::cpu::{cluster_metrics, core_metrics};
+use super::dvfs::DvfsTables;
+use super::energy::{power_tree, EnergyBreakdown};
+use super::ffi::IOReport;
+use super::gpu::gpu_metrics;
+use super::pmgr;
+use crate::error::Error;
+use crate::sources::{Capability, MetricSource, SampleContext};
+use crate::types::*;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// CPU cluster residency, GPU/ANE, memory bandwidth and power figures from
+/// IOReport.
+#[derive(Default)]
+pub struct IOReportSource {
+    ioreport: Option<IOReport>,
+    /// CPU and GPU DVFS tables, read once at init
+    dvfs: DvfsTables,
+    /// Extra channels passed through to `raw_channels`
+    raw_channels: Vec<ChannelSpec>,
+}
+
+impl IOReportSource {
+    pub fn new() -> Self {
+        Self {
+            ioreport: None,
+            dvfs: DvfsTables::default(),
+            raw_channels: Vec::new(),
+        }
+    }
+
+    /// Also subscribe to `specs` ("Group/Subgroup/Channel") and report their
+    /// deltas unprocessed in `raw_channels`. Channels the chip does not have
+    /// are left out.
+    pub fn with_raw_channels<S: AsRef<str>>(mut self, specs: &[S]) -> Result<Self, Error> {
+        for spec in specs {
+            self.raw_channels.push(ChannelSpec::parse(spec.as_ref())?);
+        }
+        Ok(self)
+    }
+
+    /// Every channel the system advertises, or only those of `group`,
+    /// sorted by group, subgroup and channel.
+    pub fn list_channels(group: Option<&str>) -> Result<Vec<IOReportChannelInfo>, Error> {
+        IOReport::list_channels(group).map_err(Error::Unavailable)
+    }
+
+    /// Sample every channel (or those of `group`) twice, `interval` apart,
+    /// and write the deltas to `path` as a JSON fixture. Returns the number
+    /// of channels written.
+    pub fn dump_fixture(
+        path: &Path,
+        group: Option<&str>,
+        interval: Duration,
+    ) -> Result<usize, Error> {
+        let mut ioreport = match group {
+            Some(group) => IOReport::new(&[(group, None)]),
+            None => IOReport::all(),
+        }
+        .map_err(Error::Unavailable)?;
+
+        ioreport.get_sample().map_err(Error::Read)?;
+        let start = Instant::now();
+        std::thread::sleep(interval);
+        let samples = ioreport.get_sample().map_err(Error::Read)?;
+
+        let fixture = IOReportFixture {
+            interval_ms: start.elapsed().as_millis() as u64,
+            samples,
+        };
+        let json = serde_json::to_string_pretty(&fixture)
+            .map_err(|e| Error::Read(format!("failed to serialize fixture: {}", e)))?;
+        std::fs::write(path, json + "\n")
+            .map_err(|e| Error::Read(format!("{}: {}", path.display(), e)))?;
+        Ok(fixture.samples.len())
+    }
+}
+
+impl MetricSource for IOReportSource {
+    fn name(&self) -> &'static str {
+        "ioreport"
+    }
+
+    fn init(&mut self) -> Result<(), Error> {
+        let groups = subscription_groups(CHANNEL_GROUPS, &self.raw_channels);
+        self.ioreport = Some(IOReport::new(&groups).map_err(Error::Unavailable)?);
+        self.dvfs = pmgr::dvfs_tables();
+        Ok(())
+    }
+
+    fn capabilities(&self) -> &'static [Capability] {
+        &[
+            Capability::CpuClusters,
+            Capability::Gpu,
+            Capability::Ane,
+            Capability::Bandwidth,
+            Capability::Power,
+            Capability::RawChannels,
+        ]
+    }
+
+    fn sample(&mut self, ctx: &SampleContext, out: &mut AllMetrics) -> Result<(), Error> {
+        let Some(ref mut ioreport) = self.ioreport else {
+            return Ok(());
+        };
+
+        let samples = ioreport.get_sample().map_err(Error::Read)?;
+        out.cpu_clusters = cluster_metrics(&samples, &self.dvfs);
+        out.cpu_cores = core_metrics(&samples, &self.dvfs);
+
+        let system = &mut out.system;
+        let tree = power_tree(&samples, ctx.interval_secs());
+        let energy = EnergyBreakdown::from_tree(&tree);
+        let total = energy.total();
+        system.cpu_power_watts = energy.cpu.watts;
+        system.gpu_power_watts = energy.gpu.watts;
+        system.ane_power_watts = energy.ane.watts;
+        system.dram_power_watts = energy.dram.watts;
+        system.total_power_watts = total.watts;
+        system.cpu_energy_joules = energy.cpu.joules;
+        system.gpu_energy_joules = energy.gpu.joules;
+        system.ane_energy_joules = energy.ane.joules;
+        system.dram_energy_joules = energy.dram.joules;
+        system.total_energy_joules = total.joules;
+        if let Some(gpu) = gpu_metrics(&samples, &self.dvfs.gpu_mhz) {
+            out.gpu = gpu;
+        }
+        out.memory_bandwidth = memory_bandwidth(&samples, ctx.interval_secs());
+        out.gpu.power_watts = energy.gpu.watts;
+        out.ane.power_watts = energy.ane.watts;
+        out.power_tree = tree;
+
+        for spec in &self.raw_channels {
+            if let Some(sample) = samples.iter().find(|s| spec.matches(s)) {
+                out.raw_channels
+                    .insert(spec.to_string(), sample.data.clone());
+            }
+        }
+        Ok(())
+    }
+}
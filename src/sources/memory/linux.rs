@@ -0,0 +1,211 @@
+use super::MemoryStats;
+use crate::error::Error;
+use crate::sources::procfs;
+use crate::types::{MemoryMetrics, MemoryPressure};
+use std::collections::HashMap;
+
+// PSI avg10 thresholds (percent of wall time stalled on memory)
+const PSI_SOME_WARN_PCT: f64 = 10.0;
+const PSI_FULL_CRITICAL_PCT: f64 = 5.0;
+
+impl MemoryStats {
+    pub fn get_metrics(&mut self) -> Result<MemoryMetrics, Error> {
+        let meminfo =
+            procfs::parse_key_values(&procfs::read("/proc/meminfo").map_err(Error::Read)?);
+        let vmstat = procfs::parse_key_values(&procfs::read("/proc/vmstat").map_err(Error::Read)?);
+
+        let mut metrics = self.metrics_from_tables(&meminfo, &vmstat);
+        metrics.compressed_bytes += zram_used_bytes();
+        metrics.pressure = get_memory_pressure();
+        Ok(metrics)
+    }
+
+    /// Everything /proc/meminfo and /proc/vmstat provide; zram and pressure
+    /// come from elsewhere.
+    fn metrics_from_tables(
+        &mut self,
+        meminfo: &HashMap<String, u64>,
+        vmstat: &HashMap<String, u64>,
+    ) -> MemoryMetrics {
+        let field = |key: &str| meminfo.get(key).copied().unwrap_or(0);
+
+        let total = match field("MemTotal") {
+            0 => self.total_memory,
+            total => total,
+        };
+        let free = field("MemFree");
+
+        // Same notion as procps `free`: everything the kernel can't hand out
+        // without reclaiming. Older kernels lack MemAvailable.
+        let available = meminfo
+            .get("MemAvailable")
+            .copied()
+            .unwrap_or_else(|| free + field("Buffers") + field("Cached") + field("SReclaimable"));
+        let used = total.saturating_sub(available);
+
+        // Closest equivalent to macOS wired memory: pages that can never be
+        // reclaimed or swapped, including kernel allocations
+        let wired =
+            field("Unevictable") + field("SUnreclaim") + field("KernelStack") + field("PageTables");
+
+        let swap_total = field("SwapTotal");
+        let swap_used = swap_total.saturating_sub(field("SwapFree"));
+
+        // pgpgin/pgpgout are in KiB; report pages like host_statistics64 does
+        let counter = |key: &str| vmstat.get(key).copied().unwrap_or(0);
+        let pageins = counter("pgpgin") * 1024 / self.page_size;
+        let pageouts = counter("pgpgout") * 1024 / self.page_size;
+        let (page_ins, page_outs, page_faults) =
+            self.page_deltas(pageins, pageouts, counter("pgfault"));
+
+        MemoryMetrics {
+            total_bytes: total,
+            used_bytes: used,
+            free_bytes: free,
+            active_bytes: field("Active"),
+            wired_bytes: wired,
+            compressed_bytes: field("Zswap"),
+            swap_used_bytes: swap_used,
+            swap_total_bytes: swap_total,
+            page_ins,
+            page_outs,
+            page_faults,
+            pressure: MemoryPressure::Normal,
+        }
+    }
+}
+
+/// Memory held by zram devices, compressed data plus allocator overhead.
+fn zram_used_bytes() -> u64 {
+    let Ok(entries) = std::fs::read_dir("/sys/block") else {
+        return 0;
+    };
+
+    entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("zram"))
+        .filter_map(|entry| procfs::read(entry.path().join("mm_stat")).ok())
+        // mm_stat: orig_data_size compr_data_size mem_used_total ...
+        .filter_map(|mm_stat| mm_stat.split_whitespace().nth(2)?.parse::<u64>().ok())
+        .sum()
+}
+
+fn get_memory_pressure() -> MemoryPressure {
+    let Ok(text) = procfs::read("/proc/pressure/memory") else {
+        return MemoryPressure::Normal;
+    };
+    let psi = parse_psi_avg10(&text);
+
+    if psi.get("full").copied().unwrap_or(0.0) >= PSI_FULL_CRITICAL_PCT {
+        MemoryPressure::Critical
+    } else if psi.get("some").copied().unwrap_or(0.0) >= PSI_SOME_WARN_PCT {
+        MemoryPressure::Warn
+    } else {
+        MemoryPressure::Normal
+    }
+}
+
+/// Map `some`/`full` lines of a PSI file to their avg10 value.
+fn parse_psi_avg10(text: &str) -> HashMap<&str, f64> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let kind = fields.next()?;
+            let avg10 = fields
+                .find_map(|f| f.strip_prefix("avg10="))?
+                .parse()
+                .ok()?;
+            Some((kind, avg10))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KIB: u64 = 1024;
+
+    fn tables(meminfo: &str, vmstat: &str) -> (HashMap<String, u64>, HashMap<String, u64>) {
+        (
+            procfs::parse_key_values(meminfo),
+            procfs::parse_key_values(vmstat),
+        )
+    }
+
+    #[test]
+    fn maps_meminfo_and_vmstat() {
+        let (meminfo, vmstat) = tables(
+            include_str!("../../../tests/fixtures/procfs/meminfo"),
+            include_str!("../../../tests/fixtures/procfs/vmstat"),
+        );
+        let mut stats = MemoryStats::new(4096, 0);
+        let memory = stats.metrics_from_tables(&meminfo, &vmstat);
+
+        assert_eq!(memory.total_bytes, 16_318_412 * KIB);
+        assert_eq!(memory.free_bytes, 812_344 * KIB);
+        assert_eq!(memory.used_bytes, (16_318_412 - 9_123_456) * KIB);
+        assert_eq!(memory.active_bytes, 6_012_344 * KIB);
+        // Unevictable + SUnreclaim + KernelStack + PageTables
+        assert_eq!(
+            memory.wired_bytes,
+            (65_536 + 250_000 + 18_000 + 42_000) * KIB
+        );
+        assert_eq!(memory.compressed_bytes, 20_480 * KIB);
+        assert_eq!(memory.swap_total_bytes, 8_388_604 * KIB);
+        assert_eq!(memory.swap_used_bytes, (8_388_604 - 8_126_460) * KIB);
+        // The first sample reports the counters as they are, in 4 KiB pages
+        assert_eq!(memory.page_ins, 1_000_000);
+        assert_eq!(memory.page_outs, 500_000);
+        assert_eq!(memory.page_faults, 123_456_789);
+
+        // Then deltas
+        let mut vmstat = vmstat;
+        *vmstat.get_mut("pgpgin").unwrap() += 400;
+        *vmstat.get_mut("pgfault").unwrap() += 250;
+        let memory = stats.metrics_from_tables(&meminfo, &vmstat);
+        assert_eq!(memory.page_ins, 100);
+        assert_eq!(memory.page_outs, 0);
+        assert_eq!(memory.page_faults, 250);
+    }
+
+    #[test]
+    fn older_kernels_estimate_available_memory() {
+        let (meminfo, vmstat) = tables(
+            include_str!("../../../tests/fixtures/procfs/meminfo_legacy"),
+            "",
+        );
+        let memory = MemoryStats::new(4096, 0).metrics_from_tables(&meminfo, &vmstat);
+
+        // MemFree + Buffers + Cached + SReclaimable
+        let available = 100_000 + 50_000 + 400_000 + 30_000;
+        assert_eq!(memory.used_bytes, (2_048_000 - available) * KIB);
+        assert_eq!(memory.swap_used_bytes, 0);
+        assert_eq!(memory.page_faults, 0);
+    }
+
+    #[test]
+    fn falls_back_to_sysctl_total() {
+        let memory =
+            MemoryStats::new(4096, 8 << 30).metrics_from_tables(&HashMap::new(), &HashMap::new());
+
+        assert_eq!(memory.total_bytes, 8 << 30);
+        assert_eq!(memory.used_bytes, 8 << 30);
+    }
+
+    #[test]
+    fn psi_avg10() {
+        let psi = parse_psi_avg10(include_str!(
+            "../../../tests/fixtures/procfs/pressure_memory"
+        ));
+
+        assert_eq!(psi.len(), 2);
+        assert_eq!(psi["some"], 12.5);
+        assert_eq!(psi["full"], 0.75);
+
+        // Lines without an avg10 field are skipped
+        let psi = parse_psi_avg10("some avg10=0.00 avg60=0.00\nfull total=3\nbogus\n");
+        assert_eq!(psi.len(), 1);
+        assert_eq!(psi["some"], 0.0);
+    }
+}
@@ -1,3 +1,5 @@
+use super::MemoryStats;
+use crate::error::Error;
 use crate::types::{MemoryMetrics, MemoryPressure};
 use std::ffi::CString;
 use std::mem::{self, MaybeUninit};
@@ -56,27 +58,9 @@ const HOST_VM_INFO64: i32 = 4;
 const HOST_VM_INFO64_COUNT: u32 =
     (std::mem::size_of::<VmStatistics64>() / std::mem::size_of::<i32>()) as u32;
 
-pub struct MemoryStats {
-    page_size: u64,
-    total_memory: u64,
-    prev_pageins: u64,
-    prev_pageouts: u64,
-    prev_faults: u64,
-}
-
 impl MemoryStats {
-    pub fn new(page_size: u64, total_memory: u64) -> Self {
-        Self {
-            page_size,
-            total_memory,
-            prev_pageins: 0,
-            prev_pageouts: 0,
-            prev_faults: 0,
-        }
-    }
-
-    pub fn get_metrics(&mut self) -> MemoryMetrics {
-        let stats = self.get_vm_stats();
+    pub fn get_metrics(&mut self) -> Result<MemoryMetrics, Error> {
+        let stats = self.get_vm_stats()?;
         let swap = self.get_swap_usage();
 
         let free = (stats.free_count as u64) * self.page_size;
@@ -90,30 +74,13 @@ impl MemoryStats {
         let used = active + wired + compressed + inactive - speculative;
 
         // Calculate deltas for page activity
-        let page_ins_delta = if stats.pageins >= self.prev_pageins {
-            stats.pageins - self.prev_pageins
-        } else {
-            stats.pageins
-        };
-        let page_outs_delta = if stats.pageouts >= self.prev_pageouts {
-            stats.pageouts - self.prev_pageouts
-        } else {
-            stats.pageouts
-        };
-        let page_faults_delta = if stats.faults >= self.prev_faults {
-            stats.faults - self.prev_faults
-        } else {
-            stats.faults
-        };
-
-        self.prev_pageins = stats.pageins;
-        self.prev_pageouts = stats.pageouts;
-        self.prev_faults = stats.faults;
+        let (page_ins_delta, page_outs_delta, page_faults_delta) =
+            self.page_deltas(stats.pageins, stats.pageouts, stats.faults);
 
         // Determine memory pressure
         let pressure = self.get_memory_pressure();
 
-        MemoryMetrics {
+        Ok(MemoryMetrics {
             total_bytes: self.total_memory,
             used_bytes: used,
             free_bytes: free,
@@ -126,10 +93,10 @@ impl MemoryStats {
             page_outs: page_outs_delta,
             page_faults: page_faults_delta,
             pressure,
-        }
+        })
     }
 
-    fn get_vm_stats(&self) -> VmStatistics64 {
+    fn get_vm_stats(&self) -> Result<VmStatistics64, Error> {
         unsafe {
             let mut stats = MaybeUninit::<VmStatistics64>::uninit();
             let mut count = HOST_VM_INFO64_COUNT;
@@ -142,10 +109,10 @@ impl MemoryStats {
             );
 
             if result != 0 {
-                return VmStatistics64::default();
+                return Err(Error::Read(format!("host_statistics64 failed: {}", result)));
             }
 
-            stats.assume_init()
+            Ok(stats.assume_init())
         }
     }
 
@@ -0,0 +1,69 @@
+use crate::error::Error;
+use crate::sources::{Capability, MetricSource, SampleContext};
+use crate::types::AllMetrics;
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+
+pub struct MemoryStats {
+    page_size: u64,
+    total_memory: u64,
+    prev_pageins: u64,
+    prev_pageouts: u64,
+    prev_faults: u64,
+}
+
+impl MemoryStats {
+    pub fn new(page_size: u64, total_memory: u64) -> Self {
+        Self {
+            page_size,
+            total_memory,
+            prev_pageins: 0,
+            prev_pageouts: 0,
+            prev_faults: 0,
+        }
+    }
+
+    /// Page-ins, page-outs and faults since the previous call.
+    ///
+    /// Takes cumulative counters from the platform backend. A counter that
+    /// went backwards (wrap or reset) reports its current value as the delta.
+    fn page_deltas(&mut self, pageins: u64, pageouts: u64, faults: u64) -> (u64, u64, u64) {
+        let deltas = (
+            counter_delta(pageins, self.prev_pageins),
+            counter_delta(pageouts, self.prev_pageouts),
+            counter_delta(faults, self.prev_faults),
+        );
+
+        self.prev_pageins = pageins;
+        self.prev_pageouts = pageouts;
+        self.prev_faults = faults;
+
+        deltas
+    }
+}
+
+fn counter_delta(current: u64, prev: u64) -> u64 {
+    if current >= prev {
+        current - prev
+    } else {
+        current
+    }
+}
+
+impl MetricSource for MemoryStats {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn capabilities(&self) -> &'static [Capability] {
+        &[Capability::Memory]
+    }
+
+    fn sample(&mut self, _ctx: &SampleContext, out: &mut AllMetrics) -> Result<(), Error> {
+        out.memory = self.get_metrics()?;
+        Ok(())
+    }
+}
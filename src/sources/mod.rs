@@ -1,11 +1,34 @@
-pub mod ioreport;
-pub mod smc;
-pub mod sysctl;
-pub mod memory;
-pub mod disk;
+//! Metric sources and the registry that feeds them to `MetricsCollector`.
+//!
+//! Only the source types re-exported here are public; the platform readers
+//! behind them (IOReport, SMC, procfs, ...) are implementation details.
 
-pub use ioreport::IOReport;
-pub use smc::Smc;
-pub use sysctl::SysctlInfo;
+mod ioreport;
+mod smc;
+mod sysctl;
+#[cfg(target_os = "linux")]
+mod cpu;
+mod memory;
+mod disk;
+#[cfg(target_os = "linux")]
+mod procfs;
+#[cfg(target_os = "linux")]
+mod rapl;
+mod registry;
+mod simulated;
+
+#[cfg(target_os = "macos")]
+pub use ioreport::IOReportSource;
+#[cfg(target_os = "macos")]
+pub use smc::SmcSource;
+pub use sysctl::{hostname, os_version, uptime_secs, SysctlInfo};
+#[cfg(target_os = "linux")]
+pub use cpu::CpuStats;
+#[cfg(target_os = "macos")]
+pub use sysctl::SysctlSource;
 pub use memory::MemoryStats;
 pub use disk::DiskStats;
+#[cfg(target_os = "linux")]
+pub use rapl::RaplStats;
+pub use registry::{Capability, MetricSource, SampleContext, SourceRegistry};
+pub use simulated::SimulatedSource;
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+pub fn read(path: impl AsRef<Path>) -> Result<String, String> {
+    let path = path.as_ref();
+    std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Read a single-value sysfs attribute such as `cpu0/cpu_capacity`.
+pub fn read_u64(path: impl AsRef<Path>) -> Option<u64> {
+    read(path).ok()?.trim().parse().ok()
+}
+
+/// Parse a kernel cpulist like `0-3,8,10-11`.
+pub fn parse_cpu_list(text: &str) -> Vec<u32> {
+    let mut cpus = Vec::new();
+
+    for part in text.trim().split(',').filter(|p| !p.is_empty()) {
+        match part.split_once('-') {
+            Some((start, end)) => {
+                if let (Ok(start), Ok(end)) = (start.parse::<u32>(), end.parse::<u32>()) {
+                    cpus.extend(start..=end);
+                }
+            }
+            None => cpus.extend(part.parse::<u32>().ok()),
+        }
+    }
+
+    cpus
+}
+
+/// Parse `key value [kB]` tables like /proc/meminfo and /proc/vmstat.
+///
+/// Keys lose their trailing colon, and values with a `kB` suffix are
+/// converted to bytes. Lines that don't parse are skipped.
+pub fn parse_key_values(text: &str) -> HashMap<String, u64> {
+    let mut values = HashMap::new();
+
+    for line in text.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(key), Some(value)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let Ok(value) = value.parse::<u64>() else {
+            continue;
+        };
+
+        let value = match fields.next() {
+            Some("kB") => value * 1024,
+            _ => value,
+        };
+        values.insert(key.trim_end_matches(':').to_string(), value);
+    }
+
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cpu_lists() {
+        let cases: [(&str, &[u32]); 6] = [
+            ("0-3,8,10-11\n", &[0, 1, 2, 3, 8, 10, 11]),
+            ("0\n", &[0]),
+            ("4-7", &[4, 5, 6, 7]),
+            // An offline list with no CPUs in it
+            ("\n", &[]),
+            // Malformed parts are skipped
+            ("0-1,x,3-y,5", &[0, 1, 5]),
+            ("2,,3", &[2, 3]),
+        ];
+
+        for (text, cpus) in cases {
+            assert_eq!(parse_cpu_list(text), cpus, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn meminfo_values_are_in_bytes() {
+        let values = parse_key_values(include_str!("../../tests/fixtures/procfs/meminfo"));
+
+        assert_eq!(values["MemTotal"], 16_318_412 * 1024);
+        assert_eq!(values["Active(anon)"], 3_012_000 * 1024);
+        assert_eq!(values["VmallocTotal"], 34_359_738_367 * 1024);
+        // No kB suffix: a count, not a size
+        assert_eq!(values["HugePages_Total"], 0);
+        assert!(!values.contains_key("MemTotal:"));
+    }
+
+    #[test]
+    fn vmstat_values_are_counts() {
+        let values = parse_key_values(include_str!("../../tests/fixtures/procfs/vmstat"));
+
+        assert_eq!(values.len(), 10);
+        assert_eq!(values["pgpgin"], 4_000_000);
+        assert_eq!(values["pgfault"], 123_456_789);
+    }
+
+    #[test]
+    fn skips_lines_that_do_not_parse() {
+        let values = parse_key_values("MemTotal: 1 kB\nbogus\nNaN: x kB\n\nDirty: 2 kB\n");
+
+        assert_eq!(values.len(), 2);
+        assert_eq!(values["Dirty"], 2048);
+    }
+}
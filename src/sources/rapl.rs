@@ -0,0 +1,375 @@
+use crate::error::Error;
+use crate::sources::{procfs, Capability, MetricSource, SampleContext};
+use crate::types::{AllMetrics, PowerNode};
+use std::path::PathBuf;
+
+const POWERCAP_PATH: &str = "/sys/class/powercap";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RaplDomain {
+    /// Whole socket; includes core and uncore
+    Package,
+    /// PP0, the CPU cores
+    Core,
+    /// PP1, the integrated GPU on client parts
+    Uncore,
+    Dram,
+}
+
+impl RaplDomain {
+    fn from_zone_name(name: &str) -> Option<Self> {
+        match name.trim() {
+            n if n.starts_with("package") => Some(RaplDomain::Package),
+            "core" => Some(RaplDomain::Core),
+            "uncore" => Some(RaplDomain::Uncore),
+            "dram" => Some(RaplDomain::Dram),
+            // psys covers the whole platform and would double count
+            _ => None,
+        }
+    }
+}
+
+struct RaplZone {
+    path: PathBuf,
+    /// Zone name as reported by sysfs, e.g. "package-0" or "core"
+    name: String,
+    domain: RaplDomain,
+    max_energy_uj: u64,
+    prev_energy_uj: Option<u64>,
+}
+
+/// CPU, GPU and DRAM power from the powercap RAPL energy counters.
+///
+/// `energy_uj` is root-only on most current kernels; without access the
+/// source fails to initialize.
+#[derive(Default)]
+pub struct RaplStats {
+    zones: Vec<RaplZone>,
+}
+
+impl RaplStats {
+    pub fn new() -> Self {
+        Self { zones: Vec::new() }
+    }
+}
+
+impl MetricSource for RaplStats {
+    fn name(&self) -> &'static str {
+        "rapl"
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn init(&mut self) -> Result<(), Error> {
+        let entries = std::fs::read_dir(POWERCAP_PATH)
+            .map_err(|e| Error::Unavailable(format!("{}: {}", POWERCAP_PATH, e)))?;
+
+        // intel-rapl:0 is a package, intel-rapl:0:N its subdomains
+        for entry in entries.flatten() {
+            if !entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with("intel-rapl:")
+            {
+                continue;
+            }
+
+            let path = entry.path();
+            let Ok(name) = procfs::read(path.join("name")) else {
+                continue;
+            };
+            let Some(domain) = RaplDomain::from_zone_name(&name) else {
+                continue;
+            };
+            // Skip zones whose counter we can't read
+            if procfs::read_u64(path.join("energy_uj")).is_none() {
+                continue;
+            }
+
+            self.zones.push(RaplZone {
+                max_energy_uj: procfs::read_u64(path.join("max_energy_range_uj")).unwrap_or(0),
+                path,
+                name: name.trim().to_string(),
+                domain,
+                prev_energy_uj: None,
+            });
+        }
+
+        // Stable order so packages come out as package-0, package-1, ...
+        self.zones.sort_by(|a, b| a.path.cmp(&b.path));
+
+        if self.zones.is_empty() {
+            return Err(Error::Unavailable(
+                "no readable RAPL energy counters".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn capabilities(&self) -> &'static [Capability] {
+        &[Capability::Power]
+    }
+
+    fn sample(&mut self, ctx: &SampleContext, out: &mut AllMetrics) -> Result<(), Error> {
+        let readings: Vec<Option<u64>> = self
+            .zones
+            .iter()
+            .map(|zone| procfs::read_u64(zone.path.join("energy_uj")))
+            .collect();
+        if readings.iter().all(Option::is_none) {
+            return Err(Error::Read("no RAPL energy counter could be read".into()));
+        }
+        self.account(&readings, ctx.interval_secs(), out);
+        Ok(())
+    }
+}
+
+impl RaplStats {
+    /// Turn one `energy_uj` reading per zone (`None` if unreadable) into
+    /// energy and power figures since the previous call.
+    fn account(&mut self, readings: &[Option<u64>], interval_secs: f64, out: &mut AllMetrics) {
+        let mut package_j = 0.0;
+        let mut core_j = None;
+        let mut zone_joules = vec![None; self.zones.len()];
+        let system = &mut out.system;
+
+        for (idx, (zone, &reading)) in self.zones.iter_mut().zip(readings).enumerate() {
+            let Some(energy) = reading else {
+                continue;
+            };
+            let prev = zone.prev_energy_uj.replace(energy);
+            let Some(prev) = prev else {
+                continue;
+            };
+            if interval_secs <= 0.0 {
+                continue;
+            }
+
+            let joules = energy_delta_uj(prev, energy, zone.max_energy_uj) as f64 / 1e6;
+            zone_joules[idx] = Some(joules);
+            match zone.domain {
+                RaplDomain::Package => package_j += joules,
+                RaplDomain::Core => *core_j.get_or_insert(0.0) += joules,
+                RaplDomain::Uncore => system.gpu_energy_joules += joules,
+                RaplDomain::Dram => system.dram_energy_joules += joules,
+            }
+        }
+
+        // Not every part exposes PP0; the package is the next best CPU figure
+        system.cpu_energy_joules = core_j.unwrap_or(package_j);
+        system.total_energy_joules = package_j + system.dram_energy_joules;
+        if interval_secs > 0.0 {
+            system.cpu_power_watts = system.cpu_energy_joules / interval_secs;
+            system.gpu_power_watts = system.gpu_energy_joules / interval_secs;
+            system.dram_power_watts = system.dram_energy_joules / interval_secs;
+            system.total_power_watts = system.total_energy_joules / interval_secs;
+            out.power_tree = self.power_tree(&zone_joules, interval_secs);
+        }
+    }
+
+    /// Packages with their core/uncore subzones, then DRAM. DRAM sits under
+    /// the package in sysfs but isn't included in its counter, so it is
+    /// reported on its own.
+    fn power_tree(&self, zone_joules: &[Option<f64>], interval_secs: f64) -> Vec<PowerNode> {
+        let node = |idx: usize| {
+            let joules = zone_joules[idx]?;
+            Some(PowerNode::leaf(
+                self.zones[idx].name.clone(),
+                joules / interval_secs,
+                joules,
+            ))
+        };
+        let is_subzone = |child: &RaplZone, package: &RaplZone| {
+            child
+                .path
+                .to_string_lossy()
+                .starts_with(&format!("{}:", package.path.display()))
+        };
+
+        let mut tree = Vec::new();
+        for (idx, package) in self.zones.iter().enumerate() {
+            if package.domain != RaplDomain::Package {
+                continue;
+            }
+            let Some(own) = zone_joules[idx] else {
+                continue;
+            };
+            let children = self
+                .zones
+                .iter()
+                .enumerate()
+                .filter(|(_, zone)| {
+                    matches!(zone.domain, RaplDomain::Core | RaplDomain::Uncore)
+                        && is_subzone(zone, package)
+                })
+                .filter_map(|(child, _)| node(child))
+                .collect();
+            tree.push(PowerNode::parent(
+                package.name.clone(),
+                Some((own / interval_secs, own)),
+                children,
+            ));
+        }
+        tree.extend(
+            (0..self.zones.len())
+                .filter(|&idx| self.zones[idx].domain == RaplDomain::Dram)
+                .filter_map(node),
+        );
+        tree
+    }
+}
+
+/// Energy consumed between two readings of a counter that wraps at
+/// `max_energy_uj`. A counter that went backwards with no known range was
+/// reset, so everything it has counted since is new.
+fn energy_delta_uj(prev: u64, current: u64, max_energy_uj: u64) -> u64 {
+    if current >= prev {
+        current - prev
+    } else {
+        max_energy_uj.saturating_sub(prev) + current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::assert_close;
+
+    #[test]
+    fn energy_deltas() {
+        // (prev, current, max, delta)
+        let cases = [
+            // Normal delta
+            (1_000, 4_500, 262_143_328_850, 3_500),
+            (7, 7, 262_143_328_850, 0),
+            // Wrapped across max
+            (262_143_000_000, 500, 262_143_328_850, 329_350),
+            // No range known
+            (1_000, 4_500, 0, 3_500),
+            (1_000, 400, 0, 400),
+            // Counter reset under a known range that prev already exceeds
+            (5_000, 400, 4_000, 400),
+        ];
+
+        for (prev, current, max, delta) in cases {
+            assert_eq!(
+                energy_delta_uj(prev, current, max),
+                delta,
+                "prev {} current {} max {}",
+                prev,
+                current,
+                max
+            );
+        }
+    }
+
+    fn zone(path: &str, name: &str) -> RaplZone {
+        RaplZone {
+            path: PathBuf::from(POWERCAP_PATH).join(path),
+            name: name.to_string(),
+            domain: RaplDomain::from_zone_name(name).unwrap(),
+            max_energy_uj: 262_143_328_850,
+            prev_energy_uj: None,
+        }
+    }
+
+    fn client_zones() -> RaplStats {
+        RaplStats {
+            zones: vec![
+                zone("intel-rapl:0", "package-0"),
+                zone("intel-rapl:0:0", "core"),
+                zone("intel-rapl:0:1", "uncore"),
+                zone("intel-rapl:0:2", "dram"),
+            ],
+        }
+    }
+
+    /// Prime the counters with `first`, then account `second` over 2 s.
+    fn account_twice(
+        rapl: &mut RaplStats,
+        first: &[Option<u64>],
+        second: &[Option<u64>],
+    ) -> AllMetrics {
+        rapl.account(first, 2.0, &mut AllMetrics::default());
+        let mut out = AllMetrics::default();
+        rapl.account(second, 2.0, &mut out);
+        out
+    }
+
+    #[test]
+    fn domains_map_to_system_metrics() {
+        let mut rapl = client_zones();
+        // 20 J package, 12 J core, 3 J uncore, 4 J DRAM
+        let out = account_twice(
+            &mut rapl,
+            &[Some(0), Some(0), Some(0), Some(0)],
+            &[
+                Some(20_000_000),
+                Some(12_000_000),
+                Some(3_000_000),
+                Some(4_000_000),
+            ],
+        );
+        let system = &out.system;
+
+        assert_close(system.cpu_energy_joules, 12.0);
+        assert_close(system.cpu_power_watts, 6.0);
+        assert_close(system.gpu_energy_joules, 3.0);
+        assert_close(system.gpu_power_watts, 1.5);
+        assert_close(system.dram_energy_joules, 4.0);
+        assert_close(system.dram_power_watts, 2.0);
+        // The package already includes core and uncore; DRAM comes on top
+        assert_close(system.total_energy_joules, 24.0);
+        assert_close(system.total_power_watts, 12.0);
+
+        let names: Vec<&str> = out.power_tree.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, ["package-0", "dram"]);
+        let children: Vec<&str> = out.power_tree[0]
+            .children
+            .iter()
+            .map(|n| n.name.as_str())
+            .collect();
+        assert_eq!(children, ["core", "uncore", PowerNode::REMAINDER]);
+        assert_close(out.power_tree[0].watts, 10.0);
+        assert_close(out.power_tree[0].children[2].joules, 5.0);
+    }
+
+    #[test]
+    fn package_stands_in_for_missing_core() {
+        let mut rapl = RaplStats {
+            zones: vec![
+                zone("intel-rapl:0", "package-0"),
+                zone("intel-rapl:1", "package-1"),
+            ],
+        };
+        let out = account_twice(
+            &mut rapl,
+            &[Some(1_000_000), Some(0)],
+            &[Some(9_000_000), Some(6_000_000)],
+        );
+
+        assert_close(out.system.cpu_energy_joules, 14.0);
+        assert_close(out.system.total_power_watts, 7.0);
+        assert_eq!(out.power_tree.len(), 2);
+    }
+
+    #[test]
+    fn unreadable_zones_are_skipped() {
+        let mut rapl = client_zones();
+        let out = account_twice(
+            &mut rapl,
+            &[Some(0), Some(0), Some(0), Some(0)],
+            &[Some(20_000_000), Some(12_000_000), None, Some(4_000_000)],
+        );
+
+        assert_close(out.system.gpu_energy_joules, 0.0);
+        let children: Vec<&str> = out.power_tree[0]
+            .children
+            .iter()
+            .map(|n| n.name.as_str())
+            .collect();
+        assert_eq!(children, ["core", PowerNode::REMAINDER]);
+    }
+}
@@ -0,0 +1,144 @@
+use crate::error::Error;
+use crate::sources::SysctlInfo;
+use crate::types::AllMetrics;
+use std::time::Duration;
+
+/// A slice of `AllMetrics` that a source is allowed to fill in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    Memory,
+    /// `cpu_clusters` and `cpu_cores`
+    CpuClusters,
+    Gpu,
+    Ane,
+    Disks,
+    /// `memory_bandwidth`
+    Bandwidth,
+    /// The `SystemMetrics` per-component power and energy fields, and
+    /// `power_tree`
+    Power,
+    /// `system.wall_power_watts`, `adapter_power_watts` and
+    /// `battery_power_watts`; the collector derives `unattributed_power_watts`
+    WallPower,
+    /// `system.thermal_pressure`, mirrored into `thermal.pressure`
+    Thermal,
+    /// `thermal.sensors` and `thermal.fans`; the collector adds the group
+    /// summaries and their peaks
+    Sensors,
+    /// `raw_channels`
+    RawChannels,
+}
+
+/// Per-sample information handed to every source.
+#[derive(Debug, Clone, Copy)]
+pub struct SampleContext {
+    /// Measured time since the previous sample.
+    pub interval: Duration,
+}
+
+impl SampleContext {
+    pub fn interval_secs(&self) -> f64 {
+        self.interval.as_secs_f64()
+    }
+}
+
+pub trait MetricSource: Send {
+    /// Short identifier, as accepted by `--disable-source`.
+    fn name(&self) -> &'static str;
+
+    /// Acquire OS handles. Sources that fail here are reported as failed and
+    /// never sampled.
+    fn init(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Whether the source reads hardware that only some machines have (RAPL
+    /// counters, the SMC). Strict mode tolerates an optional source failing
+    /// to initialize, unless the caller added it explicitly.
+    fn optional(&self) -> bool {
+        false
+    }
+
+    /// The parts of `AllMetrics` this source contributes. Anything else it
+    /// writes during `sample` is ignored by the collector.
+    fn capabilities(&self) -> &'static [Capability];
+
+    /// Take a sample and write this source's slice into `out`. On error the
+    /// collector discards `out` and marks the source degraded.
+    fn sample(&mut self, ctx: &SampleContext, out: &mut AllMetrics) -> Result<(), Error>;
+}
+
+/// Ordered set of sources for a `MetricsCollector`.
+///
+/// Sources are sampled in registration order; registering a source whose
+/// name is already taken replaces the existing one in place.
+#[derive(Default)]
+pub struct SourceRegistry {
+    sources: Vec<Box<dyn MetricSource>>,
+}
+
+impl SourceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The sources bustop uses on the current platform.
+    #[cfg(target_os = "macos")]
+    pub fn platform_default(sysctl_info: &SysctlInfo) -> Self {
+        use crate::sources::{DiskStats, IOReportSource, MemoryStats, SmcSource, SysctlSource};
+
+        let mut registry = Self::new();
+        registry.register(Box::new(IOReportSource::new()));
+        registry.register(Box::new(SmcSource::new(&sysctl_info.cpu_brand)));
+        registry.register(Box::new(MemoryStats::new(
+            sysctl_info.page_size,
+            sysctl_info.physical_memory,
+        )));
+        registry.register(Box::new(DiskStats::new()));
+        registry.register(Box::new(SysctlSource));
+        registry
+    }
+
+    /// The sources bustop uses on the current platform.
+    #[cfg(target_os = "linux")]
+    pub fn platform_default(sysctl_info: &SysctlInfo) -> Self {
+        use crate::sources::{CpuStats, DiskStats, MemoryStats, RaplStats};
+
+        let mut registry = Self::new();
+        registry.register(Box::new(CpuStats::new()));
+        registry.register(Box::new(MemoryStats::new(
+            sysctl_info.page_size,
+            sysctl_info.physical_memory,
+        )));
+        registry.register(Box::new(DiskStats::new()));
+        registry.register(Box::new(RaplStats::new()));
+        registry
+    }
+
+    pub fn register(&mut self, source: Box<dyn MetricSource>) {
+        match self.sources.iter().position(|s| s.name() == source.name()) {
+            Some(idx) => self.sources[idx] = source,
+            None => self.sources.push(source),
+        }
+    }
+
+    pub fn disable(&mut self, name: &str) -> Result<(), Error> {
+        let before = self.sources.len();
+        self.sources.retain(|s| s.name() != name);
+        if self.sources.len() == before {
+            return Err(Error::UnknownSource {
+                name: name.to_string(),
+                available: self.names(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.sources.iter().map(|s| s.name()).collect()
+    }
+
+    pub(crate) fn into_sources(self) -> Vec<Box<dyn MetricSource>> {
+        self.sources
+    }
+}
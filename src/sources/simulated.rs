@@ -0,0 +1,612 @@
+use crate::error::Error;
+use crate::sources::{Capability, MetricSource, SampleContext, SysctlInfo};
+use crate::types::*;
+use std::time::Duration;
+
+const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+const MIB: f64 = 1024.0 * 1024.0;
+const TOTAL_MEMORY: u64 = 16 * 1024 * 1024 * 1024;
+const PAGE_SIZE: u64 = 16384;
+
+/// Shape of a simulated series over sample ticks.
+#[derive(Debug, Clone, Copy)]
+enum Profile {
+    Sine {
+        base: f64,
+        amplitude: f64,
+        period: u64,
+    },
+    Step {
+        low: f64,
+        high: f64,
+        period: u64,
+    },
+    Burst {
+        base: f64,
+        peak: f64,
+        every: u64,
+        length: u64,
+    },
+}
+
+impl Profile {
+    fn at(&self, tick: u64) -> f64 {
+        match *self {
+            Profile::Sine {
+                base,
+                amplitude,
+                period,
+            } => {
+                let phase = (tick % period) as f64 / period as f64;
+                base + amplitude * (phase * std::f64::consts::TAU).sin()
+            }
+            Profile::Step { low, high, period } => {
+                if (tick / period) % 2 == 0 {
+                    low
+                } else {
+                    high
+                }
+            }
+            Profile::Burst {
+                base,
+                peak,
+                every,
+                length,
+            } => {
+                if tick % every < length {
+                    peak
+                } else {
+                    base
+                }
+            }
+        }
+    }
+}
+
+// Used memory swings up to ~94% of RAM, crossing the warn/critical marks
+const MEMORY_USED_FRAC: Profile = Profile::Sine {
+    base: 0.72,
+    amplitude: 0.22,
+    period: 240,
+};
+const WIRED_GB: Profile = Profile::Step {
+    low: 1.5,
+    high: 2.5,
+    period: 300,
+};
+const FAULTS_PER_SEC: Profile = Profile::Burst {
+    base: 4000.0,
+    peak: 60000.0,
+    every: 50,
+    length: 4,
+};
+const ECPU_ACTIVE_PCT: Profile = Profile::Sine {
+    base: 35.0,
+    amplitude: 20.0,
+    period: 60,
+};
+const PCPU_ACTIVE_PCT: Profile = Profile::Burst {
+    base: 12.0,
+    peak: 85.0,
+    every: 45,
+    length: 10,
+};
+const GPU_ACTIVE_PCT: Profile = Profile::Step {
+    low: 5.0,
+    high: 70.0,
+    period: 90,
+};
+const ANE_WATTS: Profile = Profile::Burst {
+    base: 0.0,
+    peak: 1.8,
+    every: 120,
+    length: 15,
+};
+const DISK_READ_MB: Profile = Profile::Sine {
+    base: 40.0,
+    amplitude: 35.0,
+    period: 30,
+};
+const DISK_WRITE_MB: Profile = Profile::Burst {
+    base: 2.0,
+    peak: 350.0,
+    every: 40,
+    length: 3,
+};
+
+// An external disk is attached for this many ticks, then detached for as many
+const EXTERNAL_DISK_PERIOD: u64 = 150;
+
+// Die sensors as (key, name, group, offset from the SoC temperature), laid
+// out like an M1's
+const SIM_DIE_SENSORS: &[(&str, &str, &str, f64)] = &[
+    ("Tp09", "E-core 1 die temp", "E-cores", -1.0),
+    ("Tp0T", "E-core 2 die temp", "E-cores", -1.5),
+    ("Tp01", "P-core 1 die temp", "P-cores", 3.0),
+    ("Tp05", "P-core 2 die temp", "P-cores", 2.0),
+    ("Tp0D", "P-core 3 die temp", "P-cores", 2.5),
+    ("Tp0H", "P-core 4 die temp", "P-cores", 1.5),
+    ("Tg05", "GPU 1 die temp", "GPU", -2.0),
+    ("Tg0D", "GPU 2 die temp", "GPU", -2.5),
+];
+
+/// Deterministic, seedable stand-in for the hardware sources.
+///
+/// Every series is a function of the sample index plus seeded jitter, and
+/// per-interval figures (page faults, energy) use the nominal `interval`
+/// rather than the time that actually passed, so the same seed always
+/// produces the same values regardless of timing.
+pub struct SimulatedSource {
+    rng: SplitMix64,
+    tick: u64,
+    interval: Duration,
+    heat_watts: f64,
+    swap_used_bytes: f64,
+}
+
+impl SimulatedSource {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: SplitMix64(seed),
+            tick: 0,
+            interval: Duration::from_secs(1),
+            heat_watts: 0.0,
+            swap_used_bytes: 0.0,
+        }
+    }
+
+    /// Sampling interval the series are generated for (default 1s).
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Host description shown in headers while simulating.
+    pub fn host_info() -> SysctlInfo {
+        SysctlInfo {
+            cpu_brand: "bustop simulator".to_string(),
+            cpu_cores: 10,
+            cpu_cores_perf: 6,
+            cpu_cores_eff: 4,
+            physical_memory: TOTAL_MEMORY,
+            page_size: PAGE_SIZE,
+        }
+    }
+
+    /// `profile` at the current tick with +/-5% jitter.
+    fn series(&mut self, profile: Profile) -> f64 {
+        let jitter = 1.0 + (self.rng.next_f64() - 0.5) * 0.1;
+        (profile.at(self.tick) * jitter).max(0.0)
+    }
+
+    fn memory(&mut self, interval_secs: f64) -> MemoryMetrics {
+        let used_frac = self.series(MEMORY_USED_FRAC).min(0.99);
+        let total = TOTAL_MEMORY as f64;
+        let used = used_frac * total;
+
+        // Swap builds up while memory is tight and drains slowly afterwards
+        if used_frac > 0.9 {
+            self.swap_used_bytes += 64.0 * MIB;
+        } else {
+            self.swap_used_bytes = (self.swap_used_bytes - 8.0 * MIB).max(0.0);
+        }
+        let swap_total = (self.swap_used_bytes / GIB).ceil().max(1.0) * GIB;
+
+        let pressure = if used_frac > 0.93 {
+            MemoryPressure::Critical
+        } else if used_frac > 0.85 {
+            MemoryPressure::Warn
+        } else {
+            MemoryPressure::Normal
+        };
+
+        let faults = self.series(FAULTS_PER_SEC) * interval_secs;
+        MemoryMetrics {
+            total_bytes: TOTAL_MEMORY,
+            used_bytes: used as u64,
+            free_bytes: (total - used) as u64,
+            active_bytes: (used * 0.55) as u64,
+            wired_bytes: (self.series(WIRED_GB) * GIB) as u64,
+            compressed_bytes: ((used_frac - 0.8).max(0.0) * total * 0.5) as u64,
+            swap_used_bytes: self.swap_used_bytes as u64,
+            swap_total_bytes: swap_total as u64,
+            page_ins: (faults * 0.02) as u64,
+            page_outs: if used_frac > 0.9 {
+                (faults * 0.01) as u64
+            } else {
+                0
+            },
+            page_faults: faults as u64,
+            pressure,
+        }
+    }
+
+    fn cluster(
+        &mut self,
+        name: &str,
+        core_type: CoreType,
+        profile: Profile,
+        freq: (u32, u32),
+        watts: f64,
+    ) -> CpuClusterMetrics {
+        let active = self.series(profile).min(100.0);
+        let (min_mhz, max_mhz) = freq;
+        let load = active / 100.0;
+        let freq_mhz = min_mhz + ((max_mhz - min_mhz) as f64 * load) as u32;
+
+        CpuClusterMetrics {
+            name: name.to_string(),
+            die: 0,
+            core_type,
+            freq_mhz,
+            freq_cur_mhz: freq_mhz,
+            freq_max_mhz: max_mhz,
+            active_pct: active,
+            idle_pct: 100.0 - active,
+            power_watts: 0.05 + watts * load,
+            // Two-state split whose weighted average is freq_mhz
+            freq_residency: vec![
+                FreqResidency {
+                    freq_mhz: min_mhz,
+                    residency_pct: (1.0 - load) * 100.0,
+                },
+                FreqResidency {
+                    freq_mhz: max_mhz,
+                    residency_pct: load * 100.0,
+                },
+            ],
+        }
+    }
+
+    /// Cores of the 4E+6P host, sharing their cluster's load unevenly so
+    /// the first P core runs much hotter than the cluster average.
+    fn cores(&self, clusters: &[CpuClusterMetrics]) -> Vec<CpuCoreMetrics> {
+        const SHARES: [&[f64]; 2] = [&[1.6, 1.0, 0.8, 0.6], &[2.5, 1.1, 0.8, 0.6, 0.5, 0.5]];
+
+        clusters
+            .iter()
+            .zip(SHARES)
+            .flat_map(|(cluster, shares)| shares.iter().map(move |share| (cluster, share)))
+            .enumerate()
+            .map(|(id, (cluster, share))| CpuCoreMetrics {
+                id: id as u32,
+                cluster: cluster.name.clone(),
+                active_pct: (cluster.active_pct * share).min(100.0),
+                freq_mhz: cluster.freq_mhz,
+            })
+            .collect()
+    }
+
+    /// DRAM traffic that follows CPU, GPU and ANE load, plus a constant
+    /// display scan-out.
+    fn bandwidth(&self, cpu_load: f64, gpu_load: f64, ane_watts: f64) -> MemoryBandwidth {
+        let ane_load = ane_watts / 1.8;
+        let agent = |name: &str, read_gbps: f64, write_gbps: f64| AgentBandwidth {
+            name: name.to_string(),
+            read_gbps,
+            write_gbps,
+        };
+        let agents = vec![
+            agent("CPU", 1.0 + 12.0 * cpu_load, 0.4 + 4.0 * cpu_load),
+            agent("GPU", 0.5 + 25.0 * gpu_load, 0.2 + 8.0 * gpu_load),
+            agent("ANE", 6.0 * ane_load, 2.0 * ane_load),
+            agent("Display", 1.2, 0.0),
+        ];
+
+        MemoryBandwidth {
+            read_gbps: agents.iter().map(|a| a.read_gbps).sum(),
+            write_gbps: agents.iter().map(|a| a.write_gbps).sum(),
+            agents,
+        }
+    }
+
+    /// Named sensors and a single fan, all following smoothed power. Die
+    /// sensors sit around the SoC temperature, the SSD and battery further
+    /// from it. The fan idles at its minimum until the SoC passes 60 C.
+    fn thermal(&mut self) -> ThermalMetrics {
+        let soc = 34.0 + 3.2 * self.heat_watts;
+        let target = (1200.0 + (soc - 60.0).max(0.0) * 150.0).min(5800.0);
+
+        let mut sensors = Vec::new();
+        for (key, name, group, offset) in SIM_DIE_SENSORS {
+            sensors.push(SensorReading {
+                key: key.to_string(),
+                name: name.to_string(),
+                group: group.to_string(),
+                temp_c: soc + offset + self.rng.next_f64(),
+            });
+        }
+        sensors.push(SensorReading {
+            key: "TH0x".to_string(),
+            name: "SSD NAND".to_string(),
+            group: "SSD".to_string(),
+            temp_c: 32.0 + (soc - 34.0) * 0.3,
+        });
+        sensors.push(SensorReading {
+            key: "TB1T".to_string(),
+            name: "battery cell 1".to_string(),
+            group: "battery".to_string(),
+            temp_c: 30.0 + (soc - 34.0) * 0.1,
+        });
+
+        ThermalMetrics {
+            sensors,
+            fans: vec![FanMetrics {
+                id: 0,
+                // Spins up slower than it is told to
+                rpm: 1200.0 + (target - 1200.0) * 0.8,
+                min_rpm: 1200.0,
+                max_rpm: 5800.0,
+                target_rpm: target,
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn disks(&mut self) -> Vec<DiskMetrics> {
+        let read = self.series(DISK_READ_MB) * MIB;
+        let write = self.series(DISK_WRITE_MB) * MIB;
+        let busy = ((read + write) / (800.0 * MIB)).min(1.0);
+
+        let mut disks = vec![DiskMetrics {
+            name: "disk0".to_string(),
+            read_bytes_per_sec: read as u64,
+            write_bytes_per_sec: write as u64,
+            read_ops_per_sec: (read / (64.0 * 1024.0)) as u64,
+            write_ops_per_sec: (write / (128.0 * 1024.0)) as u64,
+            util_pct: Some(busy * 100.0),
+            await_ms: Some(0.1 + busy * 2.0),
+            queue_depth: Some(busy * 8.0),
+        }];
+
+        // External drive without busy-time counters, as on macOS
+        if (self.tick / EXTERNAL_DISK_PERIOD) % 2 == 1 {
+            let read = self.series(DISK_READ_MB) * MIB * 0.25;
+            disks.push(DiskMetrics {
+                name: "disk4".to_string(),
+                read_bytes_per_sec: read as u64,
+                read_ops_per_sec: (read / (256.0 * 1024.0)) as u64,
+                ..Default::default()
+            });
+        }
+
+        disks
+    }
+}
+
+impl MetricSource for SimulatedSource {
+    fn name(&self) -> &'static str {
+        "simulated"
+    }
+
+    fn capabilities(&self) -> &'static [Capability] {
+        &[
+            Capability::Memory,
+            Capability::CpuClusters,
+            Capability::Gpu,
+            Capability::Ane,
+            Capability::Bandwidth,
+            Capability::Disks,
+            Capability::Power,
+            Capability::WallPower,
+            Capability::Thermal,
+            Capability::Sensors,
+        ]
+    }
+
+    fn sample(&mut self, _ctx: &SampleContext, out: &mut AllMetrics) -> Result<(), Error> {
+        self.tick += 1;
+        let secs = self.interval.as_secs_f64();
+
+        out.memory = self.memory(secs);
+        out.cpu_clusters = vec![
+            self.cluster(
+                "E-Cluster",
+                CoreType::Efficiency,
+                ECPU_ACTIVE_PCT,
+                (744, 2064),
+                0.6,
+            ),
+            self.cluster(
+                "P-Cluster",
+                CoreType::Performance,
+                PCPU_ACTIVE_PCT,
+                (702, 3504),
+                5.5,
+            ),
+        ];
+        out.cpu_cores = self.cores(&out.cpu_clusters);
+
+        let gpu_load = self.series(GPU_ACTIVE_PCT).min(100.0) / 100.0;
+        out.gpu = GpuMetrics {
+            freq_mhz: 389 + (1009.0 * gpu_load) as u32,
+            freq_max_mhz: 1398,
+            active_pct: gpu_load * 100.0,
+            power_watts: 0.1 + 8.0 * gpu_load,
+            // Lowest and highest P-state, weighted to average out at freq_mhz
+            pstate_residency: vec![
+                PStateResidency {
+                    state: "P1".to_string(),
+                    freq_mhz: 389,
+                    residency_pct: gpu_load * (1.0 - gpu_load) * 100.0,
+                },
+                PStateResidency {
+                    state: "P5".to_string(),
+                    freq_mhz: 1398,
+                    residency_pct: gpu_load * gpu_load * 100.0,
+                },
+            ],
+        };
+        out.ane.power_watts = self.series(ANE_WATTS);
+        out.memory_bandwidth = Some(self.bandwidth(
+            out.cpu_clusters[1].active_pct / 100.0,
+            gpu_load,
+            out.ane.power_watts,
+        ));
+        out.disks = self.disks();
+
+        let system = &mut out.system;
+        system.cpu_power_watts = out.cpu_clusters.iter().map(|c| c.power_watts).sum();
+        system.gpu_power_watts = out.gpu.power_watts;
+        system.ane_power_watts = out.ane.power_watts;
+        system.dram_power_watts =
+            0.3 + 1.2 * (gpu_load + out.cpu_clusters[1].active_pct / 100.0) / 2.0;
+        system.total_power_watts = system.cpu_power_watts
+            + system.gpu_power_watts
+            + system.ane_power_watts
+            + system.dram_power_watts;
+
+        // On the adapter: the display, SSD and fabric on top of the SoC,
+        // plus conversion losses
+        let wall = (system.total_power_watts + 3.5) * 1.1;
+        system.wall_power_watts = Some(wall);
+        system.adapter_power_watts = Some(wall);
+        system.battery_power_watts = Some(0.0);
+
+        system.cpu_energy_joules = system.cpu_power_watts * secs;
+        system.gpu_energy_joules = system.gpu_power_watts * secs;
+        system.ane_energy_joules = system.ane_power_watts * secs;
+        system.dram_energy_joules = system.dram_power_watts * secs;
+        system.total_energy_joules = system.total_power_watts * secs;
+
+        let leaf = |name: &str, watts: f64| PowerNode::leaf(name, watts, watts * secs);
+        out.power_tree = vec![
+            PowerNode::parent(
+                "CPU",
+                None,
+                out.cpu_clusters
+                    .iter()
+                    .map(|c| leaf(&c.name, c.power_watts))
+                    .collect(),
+            ),
+            leaf("GPU", system.gpu_power_watts),
+            leaf("ANE", system.ane_power_watts),
+            leaf("DRAM", system.dram_power_watts),
+        ];
+
+        // Thermal state follows smoothed power, so it lags load spikes
+        self.heat_watts = self.heat_watts * 0.9 + system.total_power_watts * 0.1;
+        system.thermal_pressure = match self.heat_watts {
+            h if h < 6.0 => ThermalPressure::Nominal,
+            h if h < 9.0 => ThermalPressure::Moderate,
+            h if h < 12.0 => ThermalPressure::Heavy,
+            _ => ThermalPressure::Critical,
+        };
+        out.thermal = self.thermal();
+        Ok(())
+    }
+}
+
+/// SplitMix64; small, fast and good enough for jitter.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1).
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(seed: u64, intervals_ms: &[u64]) -> Vec<String> {
+        let mut source = SimulatedSource::new(seed).interval(Duration::from_millis(500));
+        intervals_ms
+            .iter()
+            .map(|&ms| {
+                let ctx = SampleContext {
+                    interval: Duration::from_millis(ms),
+                };
+                let mut out = AllMetrics::default();
+                source.sample(&ctx, &mut out).unwrap();
+                serde_json::to_string(&out).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_same_series_whatever_the_timing() {
+        // Long enough for the external disk to attach
+        let steady = vec![500; EXTERNAL_DISK_PERIOD as usize + 1];
+        let jittery: Vec<u64> = (0..steady.len() as u64).map(|n| 480 + n * 7 % 50).collect();
+
+        assert_eq!(series(7, &steady), series(7, &jittery));
+        assert_ne!(series(7, &steady), series(8, &steady));
+    }
+
+    /// The first `ticks` samples at the default interval.
+    fn run(seed: u64, ticks: u64) -> Vec<AllMetrics> {
+        let mut source = SimulatedSource::new(seed);
+        let ctx = SampleContext {
+            interval: source.interval,
+        };
+        (0..ticks)
+            .map(|_| {
+                let mut out = AllMetrics::default();
+                source.sample(&ctx, &mut out).unwrap();
+                out
+            })
+            .collect()
+    }
+
+    #[test]
+    fn external_disk_comes_and_goes() {
+        let samples = run(0, 2 * EXTERNAL_DISK_PERIOD + 1);
+        let attached = |tick: u64| {
+            // Sample n is taken at tick n + 1
+            samples[tick as usize - 1]
+                .disks
+                .iter()
+                .any(|disk| disk.name == "disk4")
+        };
+
+        assert!(!attached(1));
+        assert!(!attached(EXTERNAL_DISK_PERIOD - 1));
+        assert!(attached(EXTERNAL_DISK_PERIOD));
+        assert!(attached(2 * EXTERNAL_DISK_PERIOD - 1));
+        assert!(!attached(2 * EXTERNAL_DISK_PERIOD));
+        assert!(samples.iter().all(|s| s.disks[0].name == "disk0"));
+    }
+
+    #[test]
+    fn pressure_leaves_nominal_within_a_period() {
+        let Profile::Sine { period, .. } = MEMORY_USED_FRAC else {
+            unreachable!()
+        };
+
+        for seed in [0, 7, 42] {
+            let samples = run(seed, period);
+            assert!(
+                samples
+                    .iter()
+                    .any(|s| s.memory.pressure != MemoryPressure::Normal),
+                "seed {}: memory pressure stayed normal",
+                seed
+            );
+            assert!(
+                samples
+                    .iter()
+                    .any(|s| s.system.thermal_pressure != ThermalPressure::Nominal),
+                "seed {}: thermal pressure stayed nominal",
+                seed
+            );
+            // And both settle again
+            assert!(samples
+                .iter()
+                .any(|s| s.memory.pressure == MemoryPressure::Normal));
+            assert!(samples
+                .iter()
+                .any(|s| s.system.thermal_pressure == ThermalPressure::Nominal));
+        }
+    }
+}
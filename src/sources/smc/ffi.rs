@@ -0,0 +1,238 @@
+#![allow(dead_code)]
+
+use super::keys::{fourcc, fourcc_to_str};
+use super::value::{self, SmcValue};
+use crate::types::{FanMetrics, SmcKeyInfo};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::mem::MaybeUninit;
+
+const KERNEL_INDEX_SMC: i32 = 2;
+
+const SMC_CMD_READ_BYTES: u8 = 5;
+const SMC_CMD_READ_INDEX: u8 = 8;
+const SMC_CMD_READ_KEYINFO: u8 = 9;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+struct SmcKeyData {
+    key: u32,
+    vers: [u8; 6],
+    p_limit_data: [u8; 16],
+    key_info: SmcKeyInfoData,
+    result: u8,
+    status: u8,
+    data8: u8,
+    data32: u32,
+    bytes: [u8; 32],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+struct SmcKeyInfoData {
+    data_size: u32,
+    data_type: u32,
+    data_attributes: u8,
+}
+
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IOServiceGetMatchingService(master_port: u32, matching: *const c_void) -> u32;
+    fn IOServiceMatching(name: *const i8) -> *const c_void;
+    fn IOServiceOpen(service: u32, owning_task: u32, conn_type: u32, connection: *mut u32) -> i32;
+    fn IOServiceClose(connection: u32) -> i32;
+    fn IOConnectCallStructMethod(
+        connection: u32,
+        selector: u32,
+        input_struct: *const c_void,
+        input_struct_cnt: usize,
+        output_struct: *mut c_void,
+        output_struct_cnt: *mut usize,
+    ) -> i32;
+    fn mach_task_self() -> u32;
+}
+
+pub struct Smc {
+    connection: u32,
+    /// Type and size of every key read so far. They never change while the
+    /// machine is up, so each later read is a single call.
+    key_info: RefCell<HashMap<u32, SmcKeyInfoData>>,
+}
+
+impl Smc {
+    pub fn new() -> Result<Self, String> {
+        unsafe {
+            let matching = IOServiceMatching(c"AppleSMC".as_ptr());
+            if matching.is_null() {
+                return Err("Failed to create matching dictionary".into());
+            }
+
+            let service = IOServiceGetMatchingService(0, matching);
+            if service == 0 {
+                return Err("Failed to find AppleSMC service".into());
+            }
+
+            let mut connection: u32 = 0;
+            let result = IOServiceOpen(service, mach_task_self(), 0, &mut connection);
+            if result != 0 {
+                return Err(format!("Failed to open SMC connection: {}", result));
+            }
+
+            Ok(Self {
+                connection,
+                key_info: RefCell::new(HashMap::new()),
+            })
+        }
+    }
+
+    /// Send one command to the SMC and return its reply.
+    fn call(&self, input: &SmcKeyData) -> Option<SmcKeyData> {
+        unsafe {
+            let mut output = MaybeUninit::<SmcKeyData>::uninit();
+            let mut output_size = std::mem::size_of::<SmcKeyData>();
+
+            let result = IOConnectCallStructMethod(
+                self.connection,
+                KERNEL_INDEX_SMC as u32,
+                input as *const _ as *const c_void,
+                std::mem::size_of::<SmcKeyData>(),
+                output.as_mut_ptr() as *mut c_void,
+                &mut output_size,
+            );
+
+            if result != 0 {
+                return None;
+            }
+
+            Some(output.assume_init())
+        }
+    }
+
+    fn read_key_info(&self, key: u32) -> Option<SmcKeyInfoData> {
+        if let Some(info) = self.key_info.borrow().get(&key) {
+            return Some(*info);
+        }
+
+        let output = self.call(&SmcKeyData {
+            key,
+            data8: SMC_CMD_READ_KEYINFO,
+            ..Default::default()
+        })?;
+        self.key_info.borrow_mut().insert(key, output.key_info);
+        Some(output.key_info)
+    }
+
+    /// The raw bytes of `key`, cut to its size, and its type.
+    fn read_bytes(&self, key: u32) -> Option<(String, Vec<u8>)> {
+        let key_info = self.read_key_info(key)?;
+
+        let output = self.call(&SmcKeyData {
+            key,
+            key_info: SmcKeyInfoData {
+                data_size: key_info.data_size,
+                ..Default::default()
+            },
+            data8: SMC_CMD_READ_BYTES,
+            ..Default::default()
+        })?;
+
+        let size = (key_info.data_size as usize).min(output.bytes.len());
+        Some((
+            fourcc_to_str(key_info.data_type),
+            output.bytes[..size].to_vec(),
+        ))
+    }
+
+    /// `key`'s value, decoded according to the type the SMC reports for it.
+    pub fn read_key(&self, key: &str) -> Option<SmcValue> {
+        let (data_type, bytes) = self.read_bytes(fourcc(key))?;
+        value::decode(&data_type, &bytes)
+    }
+
+    /// Number of keys the SMC exposes, from `#KEY`.
+    pub fn key_count(&self) -> u32 {
+        self.read_key("#KEY")
+            .and_then(|value| value.as_f64())
+            .map(|count| count as u32)
+            .unwrap_or(0)
+    }
+
+    /// The name of the `index`-th key.
+    pub fn key_at(&self, index: u32) -> Option<String> {
+        let output = self.call(&SmcKeyData {
+            data8: SMC_CMD_READ_INDEX,
+            data32: index,
+            ..Default::default()
+        })?;
+        Some(fourcc_to_str(output.key))
+    }
+
+    /// The keys `include` accepts, in SMC order, with their type, size and
+    /// value. Keys whose info can't be read are left out; keys whose value
+    /// can't be read are listed without one.
+    pub fn keys(&self, include: impl Fn(&str) -> bool) -> Vec<SmcKeyInfo> {
+        (0..self.key_count())
+            .filter_map(|index| {
+                let key = self.key_at(index).filter(|key| include(key))?;
+                let info = self.read_key_info(fourcc(&key))?;
+                let value = self.read_key(&key);
+                Some(SmcKeyInfo {
+                    key,
+                    data_type: fourcc_to_str(info.data_type),
+                    size: info.data_size,
+                    value: value.as_ref().and_then(SmcValue::as_f64),
+                    display: value.map(|value| value.to_string()),
+                })
+            })
+            .collect()
+    }
+
+    pub fn read_temp(&self, key: &str) -> Option<f64> {
+        self.read_key(key)?.as_f64()
+    }
+
+    pub fn read_power(&self, key: &str) -> Option<f64> {
+        self.read_key(key)?.as_f64()
+    }
+
+    pub fn read_fan_speed(&self, key: &str) -> Option<f64> {
+        self.read_key(key)?.as_f64()
+    }
+
+    /// Number of fans; 0 on fanless machines.
+    pub fn fan_count(&self) -> u32 {
+        self.read_key("FNum")
+            .and_then(|value| value.as_f64())
+            .map(|count| count as u32)
+            .unwrap_or(0)
+    }
+
+    /// Speed, limits and target of every fan. Fans whose speed can't be
+    /// read are left out.
+    pub fn fans(&self) -> Vec<FanMetrics> {
+        (0..self.fan_count())
+            .filter_map(|id| {
+                let read = |suffix: &str| self.read_fan_speed(&format!("F{}{}", id, suffix));
+                Some(FanMetrics {
+                    id,
+                    rpm: read("Ac")?,
+                    min_rpm: read("Mn").unwrap_or(0.0),
+                    max_rpm: read("Mx").unwrap_or(0.0),
+                    target_rpm: read("Tg").unwrap_or(0.0),
+                })
+            })
+            .collect()
+    }
+}
+
+impl Drop for Smc {
+    fn drop(&mut self) {
+        unsafe {
+            IOServiceClose(self.connection);
+        }
+    }
+}
+
+// Make Smc Send-safe (connection handle is just an integer)
+unsafe impl Send for Smc {}
@@ -0,0 +1,71 @@
+#![cfg_attr(not(target_os = "macos"), allow(dead_code))]
+
+/// The four-character code of `key` as the SMC expects it, first character
+/// in the high byte. 0 unless `key` is exactly four bytes long.
+pub fn fourcc(key: &str) -> u32 {
+    match <[u8; 4]>::try_from(key.as_bytes()) {
+        Ok(bytes) => u32::from_be_bytes(bytes),
+        Err(_) => 0,
+    }
+}
+
+/// The key or type name encoded in `code`.
+pub fn fourcc_to_str(code: u32) -> String {
+    String::from_utf8_lossy(&code.to_be_bytes()).to_string()
+}
+
+/// Whether `key` matches the glob `pattern`, where `*` stands for any run of
+/// characters and `?` for exactly one. Case matters: `Tc0c` and `TC0C` are
+/// different keys.
+pub fn matches(pattern: &str, key: &str) -> bool {
+    fn matches_from(pattern: &[u8], key: &[u8]) -> bool {
+        match pattern.split_first() {
+            None => key.is_empty(),
+            Some((b'*', rest)) => (0..=key.len()).any(|skip| matches_from(rest, &key[skip..])),
+            Some((&c, rest)) => match key.split_first() {
+                Some((&k, key_rest)) => (c == b'?' || c == k) && matches_from(rest, key_rest),
+                None => false,
+            },
+        }
+    }
+    matches_from(pattern.as_bytes(), key.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_four_character_codes() {
+        assert_eq!(fourcc("#KEY"), 0x234b_4559);
+        assert_eq!(fourcc_to_str(0x234b_4559), "#KEY");
+        assert_eq!(fourcc_to_str(fourcc("ui8 ")), "ui8 ");
+        assert_eq!(fourcc("TC0"), 0);
+        assert_eq!(fourcc("TC0PX"), 0);
+    }
+
+    #[test]
+    fn matches_globs() {
+        let cases = [
+            ("T*", "Tp09", true),
+            ("T*", "TG0P", true),
+            ("T*", "F0Ac", false),
+            ("*", "#KEY", true),
+            ("F?Ac", "F1Ac", true),
+            ("F?Ac", "F1Mx", false),
+            ("Tp0?", "Tp0T", true),
+            ("Tp0?", "Tp0", false),
+            ("*c", "Tc0c", true),
+            ("T*P", "TC0P", true),
+            ("T*P", "TC0D", false),
+            ("TC0P", "TC0P", true),
+            ("TC0P", "Tc0p", false),
+            ("", "", true),
+            ("", "TC0P", false),
+        ];
+
+        for (pattern, key, expected) in cases {
+            assert_eq!(matches(pattern, key), expected, "{} ~ {}", pattern, key);
+        }
+    }
+}
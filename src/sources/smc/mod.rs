@@ -0,0 +1,15 @@
+//! SMC key reads. The IOKit connection is macOS-only; decoding the raw key
+//! bytes, the sensor name tables and the power arithmetic are plain Rust so they can be tested on
+//! any OS.
+
+#[cfg(target_os = "macos")]
+mod ffi;
+mod keys;
+mod power;
+mod sensors;
+#[cfg(target_os = "macos")]
+mod source;
+mod value;
+
+#[cfg(target_os = "macos")]
+pub use source::SmcSource;
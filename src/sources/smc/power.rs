@@ -0,0 +1,101 @@
+#![cfg_attr(not(target_os = "macos"), allow(dead_code))]
+
+/// Total system power, W
+pub const SYSTEM_POWER: &str = "PSTR";
+/// Power adapter input, W
+pub const ADAPTER_POWER: &str = "PDTR";
+/// Battery voltage, mV
+pub const BATTERY_VOLTAGE: &str = "B0AV";
+/// Battery current, mA; negative while discharging
+pub const BATTERY_CURRENT: &str = "B0AC";
+
+/// System-level power readings from the SMC, in watts.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SmcPower {
+    pub wall_watts: Option<f64>,
+    pub adapter_watts: Option<f64>,
+    /// Positive while discharging, negative while charging
+    pub battery_watts: Option<f64>,
+}
+
+impl SmcPower {
+    /// Combine the raw key readings. Without `PSTR`, the system draws what
+    /// the adapter supplies plus what the battery gives up (minus what it
+    /// takes while charging). Negative or non-finite power readings count
+    /// as missing.
+    pub fn from_readings(
+        system: Option<f64>,
+        adapter: Option<f64>,
+        battery_mv: Option<f64>,
+        battery_ma: Option<f64>,
+    ) -> SmcPower {
+        let watts = |reading: Option<f64>| reading.filter(|w| w.is_finite() && *w >= 0.0);
+        let adapter_watts = watts(adapter);
+        let battery_watts = match (battery_mv, battery_ma) {
+            (Some(mv), Some(ma)) if mv > 0.0 => Some(-(mv * ma) / 1e6),
+            _ => None,
+        };
+
+        let wall_watts = watts(system).or_else(|| {
+            (adapter_watts.is_some() || battery_watts.is_some())
+                .then(|| (adapter_watts.unwrap_or(0.0) + battery_watts.unwrap_or(0.0)).max(0.0))
+        });
+
+        SmcPower {
+            wall_watts,
+            adapter_watts,
+            battery_watts,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::assert_close;
+
+    #[test]
+    fn prefers_the_system_total() {
+        let power = SmcPower::from_readings(Some(14.5), Some(30.0), Some(12_600.0), Some(1_000.0));
+
+        assert_close(power.wall_watts, 14.5);
+        assert_close(power.adapter_watts, 30.0);
+        // Charging at 1 A, 12.6 V
+        assert_close(power.battery_watts, -12.6);
+    }
+
+    #[test]
+    fn on_battery_the_battery_is_the_wall() {
+        let power = SmcPower::from_readings(None, Some(0.0), Some(12_000.0), Some(-750.0));
+
+        assert_close(power.battery_watts, 9.0);
+        assert_close(power.wall_watts, 9.0);
+    }
+
+    #[test]
+    fn charging_power_is_not_system_power() {
+        // 40 W in, 12.6 W of it into the battery
+        let power = SmcPower::from_readings(None, Some(40.0), Some(12_600.0), Some(1_000.0));
+
+        assert_close(power.wall_watts, 27.4);
+    }
+
+    #[test]
+    fn desktops_have_no_battery() {
+        let power = SmcPower::from_readings(None, Some(22.0), None, None);
+
+        assert_close(power.wall_watts, 22.0);
+        assert_eq!(power.battery_watts, None);
+    }
+
+    #[test]
+    fn missing_and_bogus_readings() {
+        assert_eq!(
+            SmcPower::from_readings(None, None, None, None),
+            SmcPower::default()
+        );
+
+        let power = SmcPower::from_readings(Some(-1.0), Some(f64::NAN), Some(0.0), Some(-500.0));
+        assert_eq!(power, SmcPower::default());
+    }
+}
@@ -0,0 +1,369 @@
+#![cfg_attr(not(target_os = "macos"), allow(dead_code))]
+
+use crate::error::Error;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+const E_CORES: &str = "E-cores";
+const P_CORES: &str = "P-cores";
+const CPU: &str = "CPU";
+const GPU: &str = "GPU";
+const SSD: &str = "SSD";
+const BATTERY: &str = "battery";
+const PALM_REST: &str = "palm rest";
+const AIRFLOW: &str = "airflow";
+const WIRELESS: &str = "wireless";
+/// Group of user-added sensors that don't name one
+const OTHER: &str = "other";
+
+/// (key, name, group) rows, in display order.
+type Table = &'static [(&'static str, &'static str, &'static str)];
+
+const M1: Table = &[
+    ("Tp09", "E-core 1 die temp", E_CORES),
+    ("Tp0T", "E-core 2 die temp", E_CORES),
+    ("Tp01", "P-core 1 die temp", P_CORES),
+    ("Tp05", "P-core 2 die temp", P_CORES),
+    ("Tp0D", "P-core 3 die temp", P_CORES),
+    ("Tp0H", "P-core 4 die temp", P_CORES),
+    ("Tp0L", "P-core 5 die temp", P_CORES),
+    ("Tp0P", "P-core 6 die temp", P_CORES),
+    ("Tp0X", "P-core 7 die temp", P_CORES),
+    ("Tp0b", "P-core 8 die temp", P_CORES),
+    ("Tg05", "GPU 1 die temp", GPU),
+    ("Tg0D", "GPU 2 die temp", GPU),
+    ("Tg0L", "GPU 3 die temp", GPU),
+    ("Tg0T", "GPU 4 die temp", GPU),
+];
+
+const M2: Table = &[
+    ("Tp1h", "E-core 1 die temp", E_CORES),
+    ("Tp1t", "E-core 2 die temp", E_CORES),
+    ("Tp1p", "E-core 3 die temp", E_CORES),
+    ("Tp1l", "E-core 4 die temp", E_CORES),
+    ("Tp01", "P-core 1 die temp", P_CORES),
+    ("Tp05", "P-core 2 die temp", P_CORES),
+    ("Tp09", "P-core 3 die temp", P_CORES),
+    ("Tp0D", "P-core 4 die temp", P_CORES),
+    ("Tp0X", "P-core 5 die temp", P_CORES),
+    ("Tp0b", "P-core 6 die temp", P_CORES),
+    ("Tp0f", "P-core 7 die temp", P_CORES),
+    ("Tp0j", "P-core 8 die temp", P_CORES),
+    ("Tg0f", "GPU 1 die temp", GPU),
+    ("Tg0j", "GPU 2 die temp", GPU),
+];
+
+const M3: Table = &[
+    ("Te05", "E-core 1 die temp", E_CORES),
+    ("Te0L", "E-core 2 die temp", E_CORES),
+    ("Te0P", "E-core 3 die temp", E_CORES),
+    ("Te0S", "E-core 4 die temp", E_CORES),
+    ("Tf04", "P-core 1 die temp", P_CORES),
+    ("Tf09", "P-core 2 die temp", P_CORES),
+    ("Tf0A", "P-core 3 die temp", P_CORES),
+    ("Tf0B", "P-core 4 die temp", P_CORES),
+    ("Tf0D", "P-core 5 die temp", P_CORES),
+    ("Tf0E", "P-core 6 die temp", P_CORES),
+    ("Tf44", "P-core 7 die temp", P_CORES),
+    ("Tf49", "P-core 8 die temp", P_CORES),
+    ("Tf4A", "P-core 9 die temp", P_CORES),
+    ("Tf4B", "P-core 10 die temp", P_CORES),
+    ("Tf4D", "P-core 11 die temp", P_CORES),
+    ("Tf4E", "P-core 12 die temp", P_CORES),
+    ("Tf14", "GPU 1 die temp", GPU),
+    ("Tf18", "GPU 2 die temp", GPU),
+    ("Tf19", "GPU 3 die temp", GPU),
+    ("Tf1A", "GPU 4 die temp", GPU),
+    ("Tf24", "GPU 5 die temp", GPU),
+    ("Tf28", "GPU 6 die temp", GPU),
+    ("Tf29", "GPU 7 die temp", GPU),
+    ("Tf2A", "GPU 8 die temp", GPU),
+];
+
+const M4: Table = &[
+    ("Te05", "E-core 1 die temp", E_CORES),
+    ("Te0S", "E-core 2 die temp", E_CORES),
+    ("Te09", "E-core 3 die temp", E_CORES),
+    ("Te0H", "E-core 4 die temp", E_CORES),
+    ("Tp01", "P-core 1 die temp", P_CORES),
+    ("Tp05", "P-core 2 die temp", P_CORES),
+    ("Tp09", "P-core 3 die temp", P_CORES),
+    ("Tp0D", "P-core 4 die temp", P_CORES),
+    ("Tp0V", "P-core 5 die temp", P_CORES),
+    ("Tp0Y", "P-core 6 die temp", P_CORES),
+    ("Tp0b", "P-core 7 die temp", P_CORES),
+    ("Tp0e", "P-core 8 die temp", P_CORES),
+    ("Tg0G", "GPU 1 die temp", GPU),
+    ("Tg0H", "GPU 2 die temp", GPU),
+    ("Tg1U", "GPU 3 die temp", GPU),
+    ("Tg1k", "GPU 4 die temp", GPU),
+    ("Tg0K", "GPU 5 die temp", GPU),
+    ("Tg0L", "GPU 6 die temp", GPU),
+    ("Tg0d", "GPU 7 die temp", GPU),
+    ("Tg0e", "GPU 8 die temp", GPU),
+];
+
+/// Sensors outside the SoC, which keep their keys across generations, and
+/// the proximity sensors of Intel Macs.
+const COMMON: Table = &[
+    ("TC0P", "CPU proximity", CPU),
+    ("TG0P", "GPU proximity", GPU),
+    ("TH0x", "SSD NAND", SSD),
+    ("TB0T", "battery max", BATTERY),
+    ("TB1T", "battery cell 1", BATTERY),
+    ("TB2T", "battery cell 2", BATTERY),
+    ("Ts0P", "palm rest left", PALM_REST),
+    ("Ts1P", "palm rest right", PALM_REST),
+    ("TaLP", "airflow left", AIRFLOW),
+    ("TaRF", "airflow right", AIRFLOW),
+    ("TW0P", "Wi-Fi module", WIRELESS),
+];
+
+/// SoC tables by chip generation. Pro, Max and Ultra parts share their
+/// generation's keys and have more of them.
+const GENERATIONS: &[(u32, Table)] = &[(1, M1), (2, M2), (3, M3), (4, M4)];
+
+/// Readings outside this range come from keys that exist but aren't wired
+/// to a sensor on this machine (0, or sentinels like 128 or -127).
+const PLAUSIBLE_TEMP_C: std::ops::Range<f64> = 1.0..125.0;
+
+/// A friendly name and group for an SMC temperature key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SensorAlias {
+    pub key: String,
+    pub name: String,
+    pub group: String,
+}
+
+/// The temperature sensors to report, in display order.
+#[derive(Debug, Clone, Default)]
+pub struct SensorTable {
+    aliases: Vec<SensorAlias>,
+}
+
+/// `sensors.toml`: a `[sensors.<KEY>]` table per sensor to rename, regroup
+/// or add.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SensorConfig {
+    #[serde(default)]
+    sensors: BTreeMap<String, SensorOverride>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SensorOverride {
+    name: Option<String>,
+    group: Option<String>,
+}
+
+impl SensorTable {
+    /// The built-in table for the chip named by `cpu_brand`, e.g. "Apple M2
+    /// Pro": its generation's SoC sensors, then the common ones. Chips newer
+    /// than the last known generation use that generation's table; unknown
+    /// chips get the common sensors only.
+    pub fn for_chip(cpu_brand: &str) -> Self {
+        let soc = chip_generation(cpu_brand).and_then(|generation| {
+            GENERATIONS
+                .iter()
+                .rev()
+                .find(|(known, _)| *known <= generation)
+                .map(|(_, table)| *table)
+        });
+
+        let aliases = soc
+            .unwrap_or_default()
+            .iter()
+            .chain(COMMON)
+            .map(|(key, name, group)| SensorAlias {
+                key: key.to_string(),
+                name: name.to_string(),
+                group: group.to_string(),
+            })
+            .collect();
+        Self { aliases }
+    }
+
+    pub fn aliases(&self) -> &[SensorAlias] {
+        &self.aliases
+    }
+
+    /// Apply the overrides in the TOML file at `path`.
+    pub fn with_config_file(mut self, path: &Path) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("{}: {}", path.display(), e)))?;
+        self.apply_config(&text)
+            .map_err(|e| Error::Config(format!("{}: {}", path.display(), e)))?;
+        Ok(self)
+    }
+
+    /// Rename or regroup the sensors `config` lists, keeping built-in values
+    /// it leaves out, and append the ones the table doesn't have, in key
+    /// order. Added sensors default to their key as name and to the "other"
+    /// group.
+    pub fn apply_config(&mut self, config: &str) -> Result<(), Error> {
+        let config: SensorConfig = toml::from_str(config)
+            .map_err(|e| Error::Config(format!("invalid sensor config: {}", e.message())))?;
+
+        for (key, entry) in config.sensors {
+            if key.len() != 4 || !key.is_ascii() {
+                return Err(Error::Config(format!(
+                    "sensor key '{}' is not four ASCII characters",
+                    key
+                )));
+            }
+
+            match self.aliases.iter_mut().find(|alias| alias.key == key) {
+                Some(alias) => {
+                    if let Some(name) = entry.name {
+                        alias.name = name;
+                    }
+                    if let Some(group) = entry.group {
+                        alias.group = group;
+                    }
+                }
+                None => self.aliases.push(SensorAlias {
+                    name: entry.name.unwrap_or_else(|| key.clone()),
+                    group: entry.group.unwrap_or_else(|| OTHER.to_string()),
+                    key,
+                }),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The generation of an Apple Silicon chip, from the "M<n>" in its brand
+/// string.
+pub fn chip_generation(cpu_brand: &str) -> Option<u32> {
+    cpu_brand
+        .split_whitespace()
+        .find_map(|word| word.strip_prefix('M')?.parse().ok())
+}
+
+/// Whether `temp_c` looks like a real sensor reading.
+pub fn plausible_temp(temp_c: f64) -> bool {
+    PLAUSIBLE_TEMP_C.contains(&temp_c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = include_str!("../../../tests/fixtures/smc/sensors.toml");
+
+    fn keys(table: &SensorTable) -> Vec<&str> {
+        table.aliases().iter().map(|a| a.key.as_str()).collect()
+    }
+
+    fn alias<'a>(table: &'a SensorTable, key: &str) -> &'a SensorAlias {
+        table.aliases().iter().find(|a| a.key == key).unwrap()
+    }
+
+    #[test]
+    fn parses_chip_generation() {
+        assert_eq!(chip_generation("Apple M1"), Some(1));
+        assert_eq!(chip_generation("Apple M2 Pro"), Some(2));
+        assert_eq!(chip_generation("Apple M3 Max"), Some(3));
+        assert_eq!(chip_generation("Apple M10 Ultra"), Some(10));
+        assert_eq!(
+            chip_generation("Intel(R) Core(TM) i9-9880H CPU @ 2.30GHz"),
+            None
+        );
+        assert_eq!(chip_generation("Apple Max"), None);
+        assert_eq!(chip_generation(""), None);
+    }
+
+    #[test]
+    fn selects_the_generation_table() {
+        let m1 = SensorTable::for_chip("Apple M1 Pro");
+        assert_eq!(alias(&m1, "Tp09").name, "E-core 1 die temp");
+        assert_eq!(alias(&m1, "Tp09").group, E_CORES);
+        // SoC sensors first, then the common ones
+        assert_eq!(keys(&m1)[..2], ["Tp09", "Tp0T"]);
+        assert_eq!(keys(&m1).len(), M1.len() + COMMON.len());
+
+        // The same key means something else on M2
+        let m2 = SensorTable::for_chip("Apple M2");
+        assert_eq!(alias(&m2, "Tp09").name, "P-core 3 die temp");
+        assert_eq!(alias(&m2, "Tp09").group, P_CORES);
+
+        let m3 = SensorTable::for_chip("Apple M3 Max");
+        assert_eq!(alias(&m3, "Tf4E").name, "P-core 12 die temp");
+    }
+
+    #[test]
+    fn newer_and_unknown_chips() {
+        let m4 = SensorTable::for_chip("Apple M4");
+        let m6 = SensorTable::for_chip("Apple M6 Pro");
+        assert_eq!(keys(&m6), keys(&m4));
+
+        let intel = SensorTable::for_chip("Intel(R) Core(TM) i7-8559U CPU @ 2.70GHz");
+        assert_eq!(keys(&intel).len(), COMMON.len());
+        assert_eq!(alias(&intel, "TC0P").group, CPU);
+    }
+
+    #[test]
+    fn config_overrides_and_extends() {
+        let mut table = SensorTable::for_chip("Apple M2");
+        let builtin = table.aliases().len();
+        table.apply_config(CONFIG).unwrap();
+
+        // Renamed, group kept
+        assert_eq!(alias(&table, "Tp01").name, "P-core 1 (hotspot)");
+        assert_eq!(alias(&table, "Tp01").group, P_CORES);
+        // Regrouped, name kept
+        assert_eq!(alias(&table, "TH0x").name, "SSD NAND");
+        assert_eq!(alias(&table, "TH0x").group, "storage");
+        // Added after the built-ins, in key order, with defaults
+        assert_eq!(table.aliases().len(), builtin + 2);
+        assert_eq!(
+            table.aliases()[builtin],
+            SensorAlias {
+                key: "TPD0".into(),
+                name: "TPD0".into(),
+                group: OTHER.into(),
+            }
+        );
+        assert_eq!(
+            table.aliases()[builtin + 1],
+            SensorAlias {
+                key: "TaLT".into(),
+                name: "ambient".into(),
+                group: "ambient".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_bad_configs() {
+        let mut table = SensorTable::for_chip("Apple M1");
+        let cases = [
+            "[sensors.TC0]\nname = \"short\"",
+            "[sensors.Tp01]\nlabel = \"typo\"",
+            "[sensor.Tp01]\nname = \"typo\"",
+            "[sensors.Tp01]\nname = 3",
+            "[sensors.Tp01",
+        ];
+
+        for config in cases {
+            assert!(
+                matches!(table.apply_config(config), Err(Error::Config(_))),
+                "{}",
+                config
+            );
+        }
+        assert!(table.apply_config("").is_ok());
+    }
+
+    #[test]
+    fn filters_implausible_readings() {
+        assert!(plausible_temp(45.5));
+        assert!(plausible_temp(1.0));
+        assert!(!plausible_temp(0.0));
+        assert!(!plausible_temp(-127.0));
+        assert!(!plausible_temp(128.0));
+        assert!(!plausible_temp(f64::NAN));
+    }
+}
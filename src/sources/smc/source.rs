@@ -0,0 +1,104 @@
+use super::ffi::Smc;
+use super::keys;
+use super::power::{self, SmcPower};
+use super::sensors::{plausible_temp, SensorAlias, SensorTable};
+use crate::error::Error;
+use crate::sources::{Capability, MetricSource, SampleContext};
+use crate::types::{AllMetrics, SensorReading, SmcKeyInfo};
+use std::path::Path;
+
+/// Named temperature sensors, fan speeds and whole-system power from the
+/// SMC.
+pub struct SmcSource {
+    smc: Option<Smc>,
+    table: SensorTable,
+    /// The table's sensors this machine has, found at init
+    sensors: Vec<SensorAlias>,
+}
+
+impl SmcSource {
+    /// A source naming sensors after the built-in table for `cpu_brand`.
+    pub fn new(cpu_brand: &str) -> Self {
+        Self {
+            smc: None,
+            table: SensorTable::for_chip(cpu_brand),
+            sensors: Vec::new(),
+        }
+    }
+
+    /// Rename, regroup or add sensors from the TOML file at `path`.
+    pub fn with_sensor_config(mut self, path: &Path) -> Result<Self, Error> {
+        self.table = self.table.with_config_file(path)?;
+        Ok(self)
+    }
+
+    /// Every SMC key with its type, size and value, or only those matching
+    /// the glob `filter` (e.g. "T*"), in SMC order.
+    pub fn list_keys(filter: Option<&str>) -> Result<Vec<SmcKeyInfo>, Error> {
+        let smc = Smc::new().map_err(Error::Unavailable)?;
+        Ok(smc.keys(|key| filter.map_or(true, |pattern| keys::matches(pattern, key))))
+    }
+}
+
+impl MetricSource for SmcSource {
+    fn name(&self) -> &'static str {
+        "smc"
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn init(&mut self) -> Result<(), Error> {
+        let smc = Smc::new().map_err(Error::Unavailable)?;
+        self.sensors = self
+            .table
+            .aliases()
+            .iter()
+            .filter(|alias| smc.read_temp(&alias.key).is_some())
+            .cloned()
+            .collect();
+        self.smc = Some(smc);
+        Ok(())
+    }
+
+    fn capabilities(&self) -> &'static [Capability] {
+        &[Capability::Sensors, Capability::WallPower]
+    }
+
+    fn sample(&mut self, _ctx: &SampleContext, out: &mut AllMetrics) -> Result<(), Error> {
+        let Some(ref smc) = self.smc else {
+            return Ok(());
+        };
+
+        let thermal = &mut out.thermal;
+        thermal.sensors = self
+            .sensors
+            .iter()
+            .filter_map(|alias| {
+                let temp_c = smc.read_temp(&alias.key).filter(|&t| plausible_temp(t))?;
+                Some(SensorReading {
+                    key: alias.key.clone(),
+                    name: alias.name.clone(),
+                    group: alias.group.clone(),
+                    temp_c,
+                })
+            })
+            .collect();
+        thermal.fans = smc.fans();
+
+        let power = SmcPower::from_readings(
+            smc.read_power(power::SYSTEM_POWER),
+            smc.read_power(power::ADAPTER_POWER),
+            smc.read_key(power::BATTERY_VOLTAGE)
+                .and_then(|value| value.as_f64()),
+            smc.read_key(power::BATTERY_CURRENT)
+                .and_then(|value| value.as_f64()),
+        );
+        let system = &mut out.system;
+        system.wall_power_watts = power.wall_watts;
+        system.adapter_power_watts = power.adapter_watts;
+        system.battery_power_watts = power.battery_watts;
+        Ok(())
+    }
+}
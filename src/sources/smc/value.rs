@@ -0,0 +1,206 @@
+#![cfg_attr(not(target_os = "macos"), allow(dead_code))]
+
+/// A decoded SMC key value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SmcValue {
+    /// `flt` and the fixed-point types (`sp78`, `fp88`, `fpe2`, ...)
+    Float(f64),
+    /// `ui8`, `ui16`, `ui32`
+    Unsigned(u64),
+    /// `si8`, `si16`
+    Signed(i64),
+    Flag(bool),
+    /// `ch8*`, without trailing NULs
+    Text(String),
+    /// `hex_` and types without a decoder
+    Bytes(Vec<u8>),
+}
+
+impl SmcValue {
+    /// The value as a number, for everything but text and raw bytes.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            SmcValue::Float(v) => Some(v),
+            SmcValue::Unsigned(v) => Some(v as f64),
+            SmcValue::Signed(v) => Some(v as f64),
+            SmcValue::Flag(v) => Some(if v { 1.0 } else { 0.0 }),
+            SmcValue::Text(_) | SmcValue::Bytes(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for SmcValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SmcValue::Float(v) => write!(f, "{:.3}", v),
+            SmcValue::Unsigned(v) => write!(f, "{}", v),
+            SmcValue::Signed(v) => write!(f, "{}", v),
+            SmcValue::Flag(v) => write!(f, "{}", v),
+            SmcValue::Text(s) => write!(f, "{:?}", s),
+            SmcValue::Bytes(bytes) => {
+                for byte in bytes {
+                    write!(f, "{:02x}", byte)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Decode `bytes` as the SMC type `data_type`, e.g. "sp78" or "ui8 ".
+/// Integer and fixed-point types are big-endian; `flt` is little-endian
+/// on Apple Silicon. Returns `None` if `bytes` is too short for the type.
+pub fn decode(data_type: &str, bytes: &[u8]) -> Option<SmcValue> {
+    let value = match data_type.trim_end() {
+        "flt" => SmcValue::Float(f32::from_le_bytes(take(bytes)?) as f64),
+        "ui8" => SmcValue::Unsigned(u64::from(take::<1>(bytes)?[0])),
+        "ui16" => SmcValue::Unsigned(u64::from(u16::from_be_bytes(take(bytes)?))),
+        "ui32" => SmcValue::Unsigned(u64::from(u32::from_be_bytes(take(bytes)?))),
+        "si8" => SmcValue::Signed(i64::from(take::<1>(bytes)?[0] as i8)),
+        "si16" => SmcValue::Signed(i64::from(i16::from_be_bytes(take(bytes)?))),
+        "flag" => SmcValue::Flag(take::<1>(bytes)?[0] != 0),
+        "ch8*" => SmcValue::Text(
+            String::from_utf8_lossy(bytes)
+                .trim_end_matches('\0')
+                .to_string(),
+        ),
+        other => match fixed_point(other) {
+            Some((signed, frac_bits)) => {
+                let raw = take::<2>(bytes)?;
+                let raw = if signed {
+                    f64::from(i16::from_be_bytes(raw))
+                } else {
+                    f64::from(u16::from_be_bytes(raw))
+                };
+                SmcValue::Float(raw / f64::from(1u32 << frac_bits))
+            }
+            None => SmcValue::Bytes(bytes.to_vec()),
+        },
+    };
+    Some(value)
+}
+
+/// Signedness and fraction bits of the 16-bit fixed-point types: `fp` or
+/// `sp`, then the integer and fraction bit counts as hex digits, e.g.
+/// `fpe2` (14.2) or `sp78` (sign + 7.8).
+fn fixed_point(data_type: &str) -> Option<(bool, u32)> {
+    let (signed, digits) = match data_type.get(..2)? {
+        "fp" => (false, &data_type[2..]),
+        "sp" => (true, &data_type[2..]),
+        _ => return None,
+    };
+    let mut digits = digits.chars().map(|c| c.to_digit(16));
+    let (Some(Some(int_bits)), Some(Some(frac_bits)), None) =
+        (digits.next(), digits.next(), digits.next())
+    else {
+        return None;
+    };
+
+    let sign_bit = u32::from(signed);
+    (int_bits + frac_bits + sign_bit == 16).then_some((signed, frac_bits))
+}
+
+/// The first `N` bytes, if there are that many.
+fn take<const N: usize>(bytes: &[u8]) -> Option<[u8; N]> {
+    bytes.get(..N)?.try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_each_type() {
+        use SmcValue::*;
+
+        let cases: &[(&str, &[u8], SmcValue)] = &[
+            // 42.5 as a little-endian f32
+            ("flt ", &[0x00, 0x00, 0x2a, 0x42], Float(42.5)),
+            ("sp78", &[0x2d, 0x80], Float(45.5)),
+            ("sp78", &[0xff, 0x00], Float(-1.0)),
+            ("fp88", &[0x0c, 0x40], Float(12.25)),
+            ("fpe2", &[0x1f, 0x40], Float(2000.0)),
+            ("sp4b", &[0x08, 0x00], Float(1.0)),
+            ("ui8 ", &[0x02], Unsigned(2)),
+            ("ui16", &[0x01, 0x2c], Unsigned(300)),
+            ("ui32", &[0x00, 0x01, 0x00, 0x00], Unsigned(65536)),
+            ("si8 ", &[0xfe], Signed(-2)),
+            ("si16", &[0xff, 0x38], Signed(-200)),
+            ("flag", &[0x01], Flag(true)),
+            ("flag", &[0x00], Flag(false)),
+            ("ch8*", b"M2Pro\0\0\0", Text("M2Pro".into())),
+            ("hex_", &[0xde, 0xad], Bytes(vec![0xde, 0xad])),
+            ("{fds", &[0x01, 0x02, 0x03], Bytes(vec![0x01, 0x02, 0x03])),
+        ];
+
+        for (data_type, bytes, expected) in cases {
+            assert_eq!(
+                decode(data_type, bytes).as_ref(),
+                Some(expected),
+                "{} {:02x?}",
+                data_type,
+                bytes
+            );
+        }
+    }
+
+    #[test]
+    fn ignores_bytes_past_the_type() {
+        assert_eq!(
+            decode("ui16", &[0x01, 0x2c, 0xff, 0xff]),
+            Some(SmcValue::Unsigned(300))
+        );
+        assert_eq!(
+            decode("sp78", &[0x2d, 0x80, 0xff]),
+            Some(SmcValue::Float(45.5))
+        );
+    }
+
+    #[test]
+    fn short_data_is_rejected() {
+        let cases: &[(&str, &[u8])] = &[
+            ("flt ", &[0x00, 0x00]),
+            ("ui8 ", &[]),
+            ("ui16", &[0x01]),
+            ("ui32", &[0x00, 0x01, 0x00]),
+            ("si16", &[0xff]),
+            ("sp78", &[0x2d]),
+            ("flag", &[]),
+        ];
+
+        for (data_type, bytes) in cases {
+            assert_eq!(decode(data_type, bytes), None, "{}", data_type);
+        }
+    }
+
+    #[test]
+    fn parses_fixed_point_types() {
+        let cases = [
+            ("fpe2", Some((false, 2))),
+            ("fp88", Some((false, 8))),
+            ("fp1f", Some((false, 15))),
+            ("sp78", Some((true, 8))),
+            ("spf0", Some((true, 0))),
+            // Bit counts must add up to 16
+            ("fp77", None),
+            ("sp88", None),
+            ("fpx2", None),
+            ("fp", None),
+            ("flt", None),
+        ];
+
+        for (data_type, expected) in cases {
+            assert_eq!(fixed_point(data_type), expected, "{}", data_type);
+        }
+    }
+
+    #[test]
+    fn numeric_view() {
+        assert_eq!(SmcValue::Float(1.5).as_f64(), Some(1.5));
+        assert_eq!(SmcValue::Unsigned(3).as_f64(), Some(3.0));
+        assert_eq!(SmcValue::Signed(-3).as_f64(), Some(-3.0));
+        assert_eq!(SmcValue::Flag(true).as_f64(), Some(1.0));
+        assert_eq!(SmcValue::Text("x".into()).as_f64(), None);
+        assert_eq!(SmcValue::Bytes(vec![1]).as_f64(), None);
+    }
+}
@@ -1,9 +1,21 @@
+#[cfg(target_os = "macos")]
+use crate::error::Error;
+#[cfg(target_os = "linux")]
+use crate::sources::procfs;
+#[cfg(target_os = "macos")]
+use crate::sources::{Capability, MetricSource, SampleContext};
+#[cfg(target_os = "macos")]
+use crate::types::{AllMetrics, ThermalPressure};
+#[cfg(target_os = "macos")]
 use std::ffi::CString;
+#[cfg(target_os = "macos")]
 use std::mem;
+#[cfg(target_os = "macos")]
 use std::ptr;
 
 pub struct SysctlInfo {
     pub cpu_brand: String,
+    #[allow(dead_code)]
     pub cpu_cores: u32,
     pub cpu_cores_perf: u32,
     pub cpu_cores_eff: u32,
@@ -11,6 +23,7 @@ pub struct SysctlInfo {
     pub page_size: u64,
 }
 
+#[cfg(target_os = "macos")]
 impl SysctlInfo {
     pub fn new() -> Result<Self, String> {
         Ok(Self {
@@ -25,6 +38,179 @@ impl SysctlInfo {
     }
 }
 
+pub fn hostname() -> String {
+    let mut buf = [0u8; 256];
+    let ret = unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) };
+    if ret != 0 {
+        return "unknown".into();
+    }
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    String::from_utf8_lossy(&buf[..len]).into_owned()
+}
+
+/// Product name and version, e.g. "macOS 14.5".
+#[cfg(target_os = "macos")]
+pub fn os_version() -> String {
+    match get_sysctl_string("kern.osproductversion") {
+        Ok(version) => format!("macOS {}", version),
+        Err(_) => "macOS".into(),
+    }
+}
+
+/// Distribution name from os-release plus the kernel release, e.g.
+/// "Ubuntu 24.04 LTS (Linux 6.8.0-31-generic)".
+#[cfg(target_os = "linux")]
+pub fn os_version() -> String {
+    let kernel = match kernel_release() {
+        Some(release) => format!("Linux {}", release),
+        None => "Linux".into(),
+    };
+    let distro = procfs::read("/etc/os-release")
+        .ok()
+        .and_then(|text| os_release_pretty_name(&text));
+
+    match distro {
+        Some(distro) => format!("{} ({})", distro, kernel),
+        None => kernel,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn os_release_pretty_name(text: &str) -> Option<String> {
+    text.lines()
+        .find_map(|line| line.strip_prefix("PRETTY_NAME="))
+        .map(|value| value.trim().trim_matches('"').to_string())
+        .filter(|name| !name.is_empty())
+}
+
+#[cfg(target_os = "linux")]
+fn kernel_release() -> Option<String> {
+    let mut uts = std::mem::MaybeUninit::<libc::utsname>::uninit();
+    if unsafe { libc::uname(uts.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    let uts = unsafe { uts.assume_init() };
+    let release = unsafe { std::ffi::CStr::from_ptr(uts.release.as_ptr()) };
+    Some(release.to_string_lossy().into_owned())
+}
+
+/// Seconds since boot, from `kern.boottime`.
+#[cfg(target_os = "macos")]
+pub fn uptime_secs() -> u64 {
+    let mut boottime = libc::timeval {
+        tv_sec: 0,
+        tv_usec: 0,
+    };
+    let mut size = mem::size_of::<libc::timeval>();
+    let ret = unsafe {
+        libc::sysctlbyname(
+            c"kern.boottime".as_ptr(),
+            &mut boottime as *mut libc::timeval as *mut libc::c_void,
+            &mut size,
+            ptr::null_mut(),
+            0,
+        )
+    };
+    if ret != 0 {
+        return 0;
+    }
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    now.saturating_sub(boottime.tv_sec as u64)
+}
+
+/// Seconds since boot, from /proc/uptime.
+#[cfg(target_os = "linux")]
+pub fn uptime_secs() -> u64 {
+    procfs::read("/proc/uptime")
+        .ok()
+        .and_then(|text| text.split_whitespace().next()?.parse::<f64>().ok())
+        .map(|secs| secs as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(target_os = "linux")]
+impl SysctlInfo {
+    /// Host information from /proc and sysconf. Core types are not
+    /// distinguished here, so the perf/eff counts stay 0.
+    pub fn new() -> Result<Self, String> {
+        let cpuinfo = procfs::read("/proc/cpuinfo").unwrap_or_default();
+        let meminfo = procfs::read("/proc/meminfo")
+            .map(|text| procfs::parse_key_values(&text))
+            .unwrap_or_default();
+
+        Ok(Self {
+            cpu_brand: linux_cpu_brand(&cpuinfo).unwrap_or_else(|| "Unknown".into()),
+            cpu_cores: sysconf(libc::_SC_NPROCESSORS_ONLN).unwrap_or(0) as u32,
+            cpu_cores_perf: 0,
+            cpu_cores_eff: 0,
+            physical_memory: meminfo.get("MemTotal").copied().unwrap_or(0),
+            page_size: sysconf(libc::_SC_PAGESIZE).unwrap_or(4096),
+        })
+    }
+}
+
+/// `model name` on x86; ARM kernels omit it, so fall back to the
+/// device-tree board model.
+#[cfg(target_os = "linux")]
+fn linux_cpu_brand(cpuinfo: &str) -> Option<String> {
+    cpuinfo
+        .lines()
+        .find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim() == "model name").then(|| value.trim().to_string())
+        })
+        .or_else(|| {
+            procfs::read("/sys/firmware/devicetree/base/model")
+                .ok()
+                .map(|model| model.trim_end_matches('\0').trim().to_string())
+        })
+        .filter(|brand| !brand.is_empty())
+}
+
+#[cfg(target_os = "linux")]
+fn sysconf(name: libc::c_int) -> Option<u64> {
+    let value = unsafe { libc::sysconf(name) };
+    (value > 0).then_some(value as u64)
+}
+
+/// Thermal pressure level from `kern.thermalpressure`.
+#[cfg(target_os = "macos")]
+pub struct SysctlSource;
+
+#[cfg(target_os = "macos")]
+impl MetricSource for SysctlSource {
+    fn name(&self) -> &'static str {
+        "sysctl"
+    }
+
+    fn capabilities(&self) -> &'static [Capability] {
+        &[Capability::Thermal]
+    }
+
+    fn sample(&mut self, _ctx: &SampleContext, out: &mut AllMetrics) -> Result<(), Error> {
+        out.system.thermal_pressure = get_thermal_pressure()?;
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn get_thermal_pressure() -> Result<ThermalPressure, Error> {
+    Ok(
+        match get_sysctl_u32("kern.thermalpressure").map_err(Error::Read)? {
+            1 => ThermalPressure::Moderate,
+            2 => ThermalPressure::Heavy,
+            3 => ThermalPressure::Critical,
+            4 => ThermalPressure::Sleeping,
+            _ => ThermalPressure::Nominal,
+        },
+    )
+}
+
+#[cfg(target_os = "macos")]
 fn get_sysctl_string(name: &str) -> Result<String, String> {
     unsafe {
         let name_c = CString::new(name).map_err(|e| e.to_string())?;
@@ -60,6 +246,7 @@ fn get_sysctl_string(name: &str) -> Result<String, String> {
     }
 }
 
+#[cfg(target_os = "macos")]
 fn get_sysctl_u32(name: &str) -> Result<u32, String> {
     unsafe {
         let name_c = CString::new(name).map_err(|e| e.to_string())?;
@@ -81,6 +268,7 @@ fn get_sysctl_u32(name: &str) -> Result<u32, String> {
     }
 }
 
+#[cfg(target_os = "macos")]
 fn get_sysctl_u64(name: &str) -> Result<u64, String> {
     unsafe {
         let name_c = CString::new(name).map_err(|e| e.to_string())?;
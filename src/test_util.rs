@@ -0,0 +1,13 @@
+//! Helpers shared by the unit tests.
+
+/// Assert that a computed figure matches `expected` up to float noise.
+/// Accepts an `Option` for readings that may be missing.
+pub fn assert_close(actual: impl Into<Option<f64>>, expected: f64) {
+    let actual = actual.into().expect("missing reading");
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
@@ -1,6 +1,12 @@
-use serde::Serialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-#[derive(Debug, Clone, Default, Serialize)]
+/// Bumped whenever a field of `MetricsRecord` is renamed, removed or changes
+/// meaning. Adding fields does not bump it.
+pub const SCHEMA_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct MemoryMetrics {
     pub total_bytes: u64,
     pub used_bytes: u64,
@@ -16,7 +22,7 @@ pub struct MemoryMetrics {
     pub pressure: MemoryPressure,
 }
 
-#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum MemoryPressure {
     #[default]
@@ -35,49 +41,396 @@ impl std::fmt::Display for MemoryPressure {
     }
 }
 
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct CpuClusterMetrics {
     pub name: String,
+    /// Die the cluster sits on; 0 on single-die chips
+    #[serde(default)]
+    pub die: u32,
+    #[serde(default)]
+    pub core_type: CoreType,
+    /// Residency-weighted average frequency over the interval
     pub freq_mhz: u32,
+    /// Frequency at the end of the interval, where the platform reports
+    /// one (`scaling_cur_freq` on Linux); 0 otherwise
+    #[serde(default)]
+    pub freq_cur_mhz: u32,
     pub freq_max_mhz: u32,
     pub active_pct: f64,
     pub idle_pct: f64,
     pub power_watts: f64,
+    /// Share of the interval spent at each frequency, lowest first
+    pub freq_residency: Vec<FreqResidency>,
+}
+
+/// One logical CPU over the last interval.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct CpuCoreMetrics {
+    /// CPU number as the OS counts them
+    pub id: u32,
+    /// Name of the `cpu_clusters` entry the core belongs to
+    pub cluster: String,
+    pub active_pct: f64,
+    /// Residency-weighted average frequency; 0 if unknown
+    pub freq_mhz: u32,
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum CoreType {
+    /// All cores are alike, or the platform does not say
+    #[default]
+    Unknown,
+    Efficiency,
+    /// Middle tier of three-tier parts
+    Mid,
+    Performance,
+}
+
+impl std::fmt::Display for CoreType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CoreType::Unknown => write!(f, "unknown"),
+            CoreType::Efficiency => write!(f, "efficiency"),
+            CoreType::Mid => write!(f, "mid"),
+            CoreType::Performance => write!(f, "performance"),
+        }
+    }
+}
+
+/// Clusters of one die or one core type taken together.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct CpuRollup {
+    /// "die0", "die1", ... or the core type
+    pub name: String,
+    pub clusters: u32,
+    /// Mean over the clusters
+    pub active_pct: f64,
+    /// Mean over the clusters that report a frequency; 0 if none do
+    pub freq_mhz: u32,
+    pub power_watts: f64,
 }
 
-#[derive(Debug, Clone, Default, Serialize)]
+impl CpuRollup {
+    /// Per-die rollups on multi-die chips, then one per core type, slowest
+    /// type first.
+    pub fn from_clusters(clusters: &[CpuClusterMetrics]) -> Vec<CpuRollup> {
+        let mut dies: Vec<u32> = clusters.iter().map(|c| c.die).collect();
+        dies.sort_unstable();
+        dies.dedup();
+        let mut types: Vec<CoreType> = clusters.iter().map(|c| c.core_type).collect();
+        types.sort_unstable();
+        types.dedup();
+
+        let mut rollups = Vec::new();
+        if dies.len() > 1 {
+            for die in dies {
+                let members: Vec<&CpuClusterMetrics> =
+                    clusters.iter().filter(|c| c.die == die).collect();
+                rollups.push(Self::of(format!("die{}", die), &members));
+            }
+        }
+        for core_type in types {
+            let members: Vec<&CpuClusterMetrics> = clusters
+                .iter()
+                .filter(|c| c.core_type == core_type)
+                .collect();
+            rollups.push(Self::of(core_type.to_string(), &members));
+        }
+        rollups
+    }
+
+    fn of(name: String, members: &[&CpuClusterMetrics]) -> CpuRollup {
+        let count = members.len();
+        let freqs: Vec<u32> = members
+            .iter()
+            .map(|c| c.freq_mhz)
+            .filter(|&mhz| mhz > 0)
+            .collect();
+
+        CpuRollup {
+            name,
+            clusters: count as u32,
+            active_pct: members.iter().map(|c| c.active_pct).sum::<f64>() / count.max(1) as f64,
+            freq_mhz: if freqs.is_empty() {
+                0
+            } else {
+                (freqs.iter().map(|&mhz| mhz as u64).sum::<u64>() / freqs.len() as u64) as u32
+            },
+            power_watts: members.iter().map(|c| c.power_watts).sum(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct FreqResidency {
+    pub freq_mhz: u32,
+    pub residency_pct: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct GpuMetrics {
+    /// Residency-weighted average frequency while active
     pub freq_mhz: u32,
     pub freq_max_mhz: u32,
     pub active_pct: f64,
     pub power_watts: f64,
+    /// Share of the interval spent in each active P-state, lowest first;
+    /// the shares add up to `active_pct`
+    #[serde(default)]
+    pub pstate_residency: Vec<PStateResidency>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PStateResidency {
+    pub state: String,
+    /// 0 when the chip's frequency table is not available
+    pub freq_mhz: u32,
+    pub residency_pct: f64,
 }
 
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct AneMetrics {
     pub power_watts: f64,
 }
 
-#[derive(Debug, Clone, Default, Serialize)]
+/// DRAM traffic over the sample interval, in GB/s (10^9 bytes).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct MemoryBandwidth {
+    /// At the memory controller, across all agents
+    pub read_gbps: f64,
+    pub write_gbps: f64,
+    /// CPU, GPU, ANE, ISP and Display first, then any other agent the chip
+    /// reports
+    pub agents: Vec<AgentBandwidth>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct AgentBandwidth {
+    pub name: String,
+    pub read_gbps: f64,
+    pub write_gbps: f64,
+}
+
+/// An IOReport channel's delta, by its `IOReportChannelFormat`. The format
+/// is implied by which field is present: `states`, `buckets` or `value`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum IOReportChannel {
+    /// Residency table, in channel order
+    State {
+        states: Vec<StateResidency>,
+    },
+    Histogram {
+        buckets: Vec<HistogramBucket>,
+    },
+    /// A single counter, e.g. energy
+    Simple {
+        value: i64,
+    },
+}
+
+/// Time spent in one state during the sample interval, in the channel's unit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct StateResidency {
+    pub name: String,
+    pub residency: i64,
+}
+
+/// Hits in one histogram bucket during the sample interval.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct HistogramBucket {
+    pub min: i64,
+    pub max: i64,
+    pub hits: i64,
+}
+
+/// How an IOReport channel reports its data.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IOReportFormat {
+    /// Formats bustop does not decode
+    #[default]
+    Unknown,
+    Simple,
+    State,
+    Histogram,
+}
+
+impl std::fmt::Display for IOReportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IOReportFormat::Unknown => write!(f, "unknown"),
+            IOReportFormat::Simple => write!(f, "simple"),
+            IOReportFormat::State => write!(f, "state"),
+            IOReportFormat::Histogram => write!(f, "histogram"),
+        }
+    }
+}
+
+/// An IOReport channel as it is advertised, without data.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct IOReportChannelInfo {
+    pub group: String,
+    pub subgroup: String,
+    pub channel: String,
+    pub format: IOReportFormat,
+    pub unit: String,
+}
+
+/// An SMC key with its type and current value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SmcKeyInfo {
+    pub key: String,
+    /// SMC type name, e.g. "flt ", "sp78", "ui8 "
+    pub data_type: String,
+    /// Value size in bytes
+    pub size: u32,
+    /// The value as a number, for numeric types
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<f64>,
+    /// The decoded value as text; `None` if the key could not be read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct DiskMetrics {
     pub name: String,
     pub read_bytes_per_sec: u64,
     pub write_bytes_per_sec: u64,
     pub read_ops_per_sec: u64,
     pub write_ops_per_sec: u64,
+    /// Extended iostat -x figures; `None` where the platform has no busy-time counters
+    pub util_pct: Option<f64>,
+    pub await_ms: Option<f64>,
+    pub queue_depth: Option<f64>,
 }
 
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct SystemMetrics {
     pub total_power_watts: f64,
     pub cpu_power_watts: f64,
     pub gpu_power_watts: f64,
     pub ane_power_watts: f64,
     pub dram_power_watts: f64,
+    /// Energy spent over the sample interval, alongside the average power above
+    #[serde(default)]
+    pub total_energy_joules: f64,
+    #[serde(default)]
+    pub cpu_energy_joules: f64,
+    #[serde(default)]
+    pub gpu_energy_joules: f64,
+    #[serde(default)]
+    pub ane_energy_joules: f64,
+    #[serde(default)]
+    pub dram_energy_joules: f64,
+    /// Whole-machine draw as the SMC measures it, including the display,
+    /// SSD, fabric and conversion losses the fields above miss
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wall_power_watts: Option<f64>,
+    /// Power adapter input; 0 on battery
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adapter_power_watts: Option<f64>,
+    /// Drawn from the battery; negative while it charges
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub battery_power_watts: Option<f64>,
+    /// `wall_power_watts` minus `total_power_watts`: what the per-component
+    /// breakdown can't account for. The two are measured differently, so
+    /// this can dip slightly below 0 under changing load.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unattributed_power_watts: Option<f64>,
     pub thermal_pressure: ThermalPressure,
 }
 
-#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq)]
+/// Named temperature sensors, their groups, fans and thermal pressure.
+/// Temperatures are in degrees Celsius; machines without sensors leave the
+/// lists empty.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ThermalMetrics {
+    #[serde(default)]
+    pub sensors: Vec<SensorReading>,
+    /// One entry per sensor group, in the order groups first appear in
+    /// `sensors`
+    #[serde(default)]
+    pub groups: Vec<SensorGroup>,
+    pub fans: Vec<FanMetrics>,
+    /// Same as `system.thermal_pressure`
+    pub pressure: ThermalPressure,
+}
+
+/// Window of `SensorGroup::peak_c`.
+pub const THERMAL_MAX_WINDOW_SECS: u64 = 60;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SensorReading {
+    /// SMC key, e.g. "Tp09"
+    pub key: String,
+    /// Friendly name, e.g. "P-core 3 die temp"
+    pub name: String,
+    /// e.g. "P-cores", "GPU", "SSD", "battery"
+    pub group: String,
+    pub temp_c: f64,
+}
+
+/// Summary of the readings of one sensor group.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SensorGroup {
+    pub name: String,
+    pub sensors: usize,
+    pub min_c: f64,
+    pub avg_c: f64,
+    pub max_c: f64,
+    /// Highest `max_c` over the last `THERMAL_MAX_WINDOW_SECS`, including
+    /// this sample
+    pub peak_c: f64,
+}
+
+impl SensorGroup {
+    /// Min, average and max of each group in `sensors`, in the order the
+    /// groups first appear. `peak_c` starts out as `max_c`.
+    pub fn from_sensors(sensors: &[SensorReading]) -> Vec<SensorGroup> {
+        let mut groups: Vec<SensorGroup> = Vec::new();
+        for sensor in sensors {
+            match groups.iter_mut().find(|g| g.name == sensor.group) {
+                Some(group) => {
+                    group.sensors += 1;
+                    group.min_c = group.min_c.min(sensor.temp_c);
+                    group.max_c = group.max_c.max(sensor.temp_c);
+                    // Running sum until the division below
+                    group.avg_c += sensor.temp_c;
+                }
+                None => groups.push(SensorGroup {
+                    name: sensor.group.clone(),
+                    sensors: 1,
+                    min_c: sensor.temp_c,
+                    avg_c: sensor.temp_c,
+                    max_c: sensor.temp_c,
+                    peak_c: 0.0,
+                }),
+            }
+        }
+        for group in &mut groups {
+            group.avg_c /= group.sensors as f64;
+            group.peak_c = group.max_c;
+        }
+        groups
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct FanMetrics {
+    pub id: u32,
+    pub rpm: f64,
+    pub min_rpm: f64,
+    pub max_rpm: f64,
+    /// What the SMC is steering towards; equals `rpm` once settled
+    pub target_rpm: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum ThermalPressure {
     #[default]
@@ -100,14 +453,149 @@ impl std::fmt::Display for ThermalPressure {
     }
 }
 
-#[derive(Debug, Clone, Default, Serialize)]
+/// One component of the power breakdown. A node's figures cover all of its
+/// children, and the leaves of a tree add up to its root.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PowerNode {
+    pub name: String,
+    pub watts: f64,
+    pub joules: f64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<PowerNode>,
+}
+
+impl PowerNode {
+    /// Name of the leaf holding what a parent reports beyond its children.
+    pub const REMAINDER: &'static str = "other";
+
+    pub fn leaf(name: impl Into<String>, watts: f64, joules: f64) -> Self {
+        Self {
+            name: name.into(),
+            watts,
+            joules,
+            children: Vec::new(),
+        }
+    }
+
+    /// A node whose figures are the sum of `children`. If the parent has its
+    /// own reading (`own_watts`, `own_joules`) and it exceeds that sum, the
+    /// difference is added as an `other` leaf so nothing is lost.
+    pub fn parent(
+        name: impl Into<String>,
+        own: Option<(f64, f64)>,
+        mut children: Vec<PowerNode>,
+    ) -> Self {
+        let mut watts: f64 = children.iter().map(|c| c.watts).sum();
+        let mut joules: f64 = children.iter().map(|c| c.joules).sum();
+
+        if let Some((own_watts, own_joules)) = own {
+            if children.is_empty() {
+                return Self::leaf(name, own_watts, own_joules);
+            }
+            // Ignore float noise when the parent is exactly its children
+            if own_joules - joules > 1e-9 {
+                children.push(Self::leaf(
+                    Self::REMAINDER,
+                    own_watts - watts,
+                    own_joules - joules,
+                ));
+                watts = own_watts;
+                joules = own_joules;
+            }
+        }
+
+        Self {
+            name: name.into(),
+            watts,
+            joules,
+            children,
+        }
+    }
+}
+
+/// How a source fared on the latest sample.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceStatus {
+    #[default]
+    Available,
+    /// Initialized, but the latest sample failed; its metrics are left at defaults
+    Degraded,
+    /// Failed to initialize and is never sampled
+    Failed,
+}
+
+impl std::fmt::Display for SourceStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SourceStatus::Available => write!(f, "available"),
+            SourceStatus::Degraded => write!(f, "degraded"),
+            SourceStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SourceHealth {
+    pub name: String,
+    pub status: SourceStatus,
+    pub last_error: Option<String>,
+    pub consecutive_failures: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct AllMetrics {
     pub timestamp_ms: u64,
     pub interval_ms: u64,
     pub memory: MemoryMetrics,
     pub cpu_clusters: Vec<CpuClusterMetrics>,
+    /// Per-die and per-core-type summaries of `cpu_clusters`; only filled
+    /// when asked for (`--cpu-rollup`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cpu_rollups: Vec<CpuRollup>,
+    /// Per-core breakdown of `cpu_clusters`, in CPU number order; empty
+    /// where the platform has no per-core counters
+    #[serde(default)]
+    pub cpu_cores: Vec<CpuCoreMetrics>,
     pub gpu: GpuMetrics,
+    /// Missing where the chip has no memory-controller byte counters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_bandwidth: Option<MemoryBandwidth>,
     pub ane: AneMetrics,
     pub disks: Vec<DiskMetrics>,
     pub system: SystemMetrics,
+    /// Every power-reporting component, top-level nodes first; empty where
+    /// the platform has no per-component energy counters
+    #[serde(default)]
+    pub power_tree: Vec<PowerNode>,
+    #[serde(default)]
+    pub thermal: ThermalMetrics,
+    /// Deltas of the channels asked for with `--ioreport-channel`, keyed by
+    /// "Group/Subgroup/Channel"
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub raw_channels: BTreeMap<String, IOReportChannel>,
+    /// One entry per registered source, in sampling order
+    #[serde(default)]
+    pub sources: Vec<SourceHealth>,
+}
+
+/// Describes the machine a record was taken on.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct HostMetadata {
+    pub hostname: String,
+    /// e.g. "macOS 14.5" or "Ubuntu 24.04 LTS (Linux 6.8.0)"
+    pub os_version: String,
+    /// CPU brand string, e.g. "Apple M2 Pro"
+    pub chip: String,
+    pub uptime_secs: u64,
+}
+
+/// One line of `--json` output: `AllMetrics` with its fields at the top
+/// level, plus the schema version and host metadata.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct MetricsRecord {
+    pub schema_version: u32,
+    pub host: HostMetadata,
+    #[serde(flatten)]
+    pub metrics: AllMetrics,
 }